@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS services (
+    id VARCHAR(255) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    server_url TEXT NOT NULL,
+    response_times INTEGER[] DEFAULT array[]::INTEGER[],
+    is_online BOOLEAN DEFAULT false
+);
+
+CREATE TABLE IF NOT EXISTS incidents (
+    id SERIAL PRIMARY KEY,
+    service_id VARCHAR(255) REFERENCES services(id),
+    service_name VARCHAR(255) NOT NULL,
+    start_time TIMESTAMP WITH TIME ZONE NOT NULL,
+    end_time TIMESTAMP WITH TIME ZONE,
+    description TEXT NOT NULL
+);
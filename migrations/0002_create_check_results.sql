@@ -0,0 +1,41 @@
+CREATE TABLE IF NOT EXISTS check_results (
+    id BIGSERIAL PRIMARY KEY,
+    service_id VARCHAR(255) NOT NULL REFERENCES services(id) ON DELETE CASCADE,
+    checked_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    response_ms INTEGER,
+    status VARCHAR(16) NOT NULL,
+    error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS check_results_service_checked_at
+    ON check_results (service_id, checked_at DESC);
+
+INSERT INTO check_results (service_id, checked_at, response_ms, status)
+SELECT s.id,
+       date_trunc('minute', NOW()) - (t.ord - 1) * INTERVAL '1 minute',
+       NULLIF(t.value, 0),
+       CASE WHEN t.value > 0 THEN 'up' ELSE 'down' END
+FROM services s, unnest(s.response_times) WITH ORDINALITY AS t(value, ord)
+WHERE NOT EXISTS (SELECT 1 FROM check_results c WHERE c.service_id = s.id);
+
+CREATE OR REPLACE FUNCTION record_legacy_check() RETURNS TRIGGER AS $$
+BEGIN
+    IF array_length(NEW.response_times, 1) > 0 THEN
+        INSERT INTO check_results (service_id, checked_at, response_ms, status)
+        VALUES (
+            NEW.id,
+            NOW(),
+            NULLIF(NEW.response_times[1], 0),
+            CASE WHEN NEW.response_times[1] > 0 THEN 'up' ELSE 'down' END
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS services_legacy_check ON services;
+CREATE TRIGGER services_legacy_check
+    AFTER UPDATE OF response_times ON services
+    FOR EACH ROW
+    WHEN (NEW.response_times IS DISTINCT FROM OLD.response_times)
+    EXECUTE FUNCTION record_legacy_check();
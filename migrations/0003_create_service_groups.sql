@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS service_groups (
+    id VARCHAR(255) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0
+);
+
+ALTER TABLE services
+    ADD COLUMN IF NOT EXISTS group_id VARCHAR(255) REFERENCES service_groups(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS services_group_id ON services (group_id);
@@ -0,0 +1,67 @@
+CREATE TABLE IF NOT EXISTS incident_updates (
+    id SERIAL PRIMARY KEY,
+    incident_id INTEGER NOT NULL REFERENCES incidents(id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL CHECK (status IN ('investigating', 'identified', 'monitoring', 'resolved')),
+    body TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS incident_updates_incident_created_at
+    ON incident_updates (incident_id, created_at);
+
+INSERT INTO incident_updates (incident_id, status, body, created_at)
+SELECT i.id, 'investigating', i.description, i.start_time
+FROM incidents i
+WHERE NOT EXISTS (SELECT 1 FROM incident_updates u WHERE u.incident_id = i.id);
+
+INSERT INTO incident_updates (incident_id, status, body, created_at)
+SELECT i.id, 'resolved', 'This incident has been resolved.', i.end_time
+FROM incidents i
+WHERE i.end_time IS NOT NULL
+AND NOT EXISTS (SELECT 1 FROM incident_updates u WHERE u.incident_id = i.id AND u.status = 'resolved');
+
+CREATE OR REPLACE FUNCTION sync_incident_end_time() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE incidents
+    SET end_time = CASE WHEN NEW.status = 'resolved' THEN NEW.created_at END
+    WHERE id = NEW.incident_id
+    AND NOT EXISTS (
+        SELECT 1 FROM incident_updates u
+        WHERE u.incident_id = NEW.incident_id
+        AND (u.created_at, u.id) > (NEW.created_at, NEW.id)
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS incident_updates_end_time ON incident_updates;
+CREATE TRIGGER incident_updates_end_time
+    AFTER INSERT ON incident_updates
+    FOR EACH ROW
+    EXECUTE FUNCTION sync_incident_end_time();
+
+CREATE OR REPLACE FUNCTION record_incident_lifecycle() RETURNS TRIGGER AS $$
+BEGIN
+    IF pg_trigger_depth() > 1 THEN
+        RETURN NEW;
+    END IF;
+
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO incident_updates (incident_id, status, body, created_at)
+        VALUES (NEW.id, 'investigating', NEW.description, NEW.start_time);
+    END IF;
+
+    IF NEW.end_time IS NOT NULL AND (TG_OP = 'INSERT' OR OLD.end_time IS NULL) THEN
+        INSERT INTO incident_updates (incident_id, status, body, created_at)
+        VALUES (NEW.id, 'resolved', 'This incident has been resolved.', NEW.end_time);
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS incidents_lifecycle ON incidents;
+CREATE TRIGGER incidents_lifecycle
+    AFTER INSERT OR UPDATE OF end_time ON incidents
+    FOR EACH ROW
+    EXECUTE FUNCTION record_incident_lifecycle();
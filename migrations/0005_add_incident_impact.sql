@@ -0,0 +1,7 @@
+-- Incidents recorded before impact levels existed are backfilled as minor;
+-- new incidents default to major.
+ALTER TABLE incidents
+    ADD COLUMN IF NOT EXISTS impact VARCHAR(16) NOT NULL DEFAULT 'minor'
+    CHECK (impact IN ('none', 'minor', 'major', 'critical'));
+
+ALTER TABLE incidents ALTER COLUMN impact SET DEFAULT 'major';
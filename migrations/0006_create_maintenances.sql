@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS maintenances (
+    id SERIAL PRIMARY KEY,
+    description TEXT NOT NULL,
+    start_time TIMESTAMP WITH TIME ZONE NOT NULL,
+    end_time TIMESTAMP WITH TIME ZONE NOT NULL,
+    CHECK (end_time > start_time)
+);
+
+CREATE TABLE IF NOT EXISTS maintenance_services (
+    maintenance_id INTEGER NOT NULL REFERENCES maintenances(id) ON DELETE CASCADE,
+    service_id VARCHAR(255) NOT NULL REFERENCES services(id) ON DELETE CASCADE,
+    PRIMARY KEY (maintenance_id, service_id)
+);
+
+CREATE INDEX IF NOT EXISTS maintenances_end_time ON maintenances (end_time);
+CREATE INDEX IF NOT EXISTS maintenance_services_service_id ON maintenance_services (service_id);
@@ -0,0 +1,8 @@
+ALTER TABLE check_results
+    ADD COLUMN IF NOT EXISTS region VARCHAR(64) NOT NULL DEFAULT 'default';
+
+ALTER TABLE services
+    ADD COLUMN IF NOT EXISTS region_quorum INTEGER CHECK (region_quorum > 0);
+
+CREATE INDEX IF NOT EXISTS check_results_service_region_checked_at
+    ON check_results (service_id, region, checked_at DESC);
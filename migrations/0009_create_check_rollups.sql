@@ -0,0 +1,30 @@
+CREATE TABLE IF NOT EXISTS check_rollups_hourly (
+    service_id VARCHAR(255) NOT NULL REFERENCES services(id) ON DELETE CASCADE,
+    region VARCHAR(64) NOT NULL,
+    hour TIMESTAMP WITH TIME ZONE NOT NULL,
+    samples BIGINT NOT NULL,
+    down BIGINT NOT NULL,
+    maintenance BIGINT NOT NULL,
+    avg_response_ms DOUBLE PRECISION,
+    min_response_ms INTEGER,
+    max_response_ms INTEGER,
+    p95_response_ms DOUBLE PRECISION,
+    PRIMARY KEY (service_id, region, hour)
+);
+
+CREATE INDEX IF NOT EXISTS check_rollups_hourly_hour ON check_rollups_hourly (hour);
+
+CREATE TABLE IF NOT EXISTS check_rollups_daily (
+    service_id VARCHAR(255) NOT NULL REFERENCES services(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    samples BIGINT NOT NULL,
+    down BIGINT NOT NULL,
+    maintenance BIGINT NOT NULL,
+    avg_response_ms DOUBLE PRECISION,
+    min_response_ms INTEGER,
+    max_response_ms INTEGER,
+    p95_response_ms DOUBLE PRECISION,
+    PRIMARY KEY (service_id, day)
+);
+
+CREATE INDEX IF NOT EXISTS check_rollups_daily_day ON check_rollups_daily (day);
@@ -0,0 +1,15 @@
+CREATE OR REPLACE FUNCTION record_legacy_check() RETURNS TRIGGER AS $$
+BEGIN
+    IF array_length(NEW.response_times, 1) > 0
+        AND array_length(NEW.response_times, 1) >= COALESCE(array_length(OLD.response_times, 1), 0) THEN
+        INSERT INTO check_results (service_id, checked_at, response_ms, status)
+        VALUES (
+            NEW.id,
+            NOW(),
+            NULLIF(NEW.response_times[1], 0),
+            CASE WHEN NEW.response_times[1] > 0 THEN 'up' ELSE 'down' END
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,5 @@
+ALTER TABLE services
+    ADD COLUMN IF NOT EXISTS check_type VARCHAR(16) NOT NULL DEFAULT 'http'
+        CHECK (check_type IN ('http', 'tcp', 'tls', 'banner')),
+    ADD COLUMN IF NOT EXISTS check_send TEXT,
+    ADD COLUMN IF NOT EXISTS check_expect TEXT;
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS service_certificates (
+    service_id VARCHAR(255) NOT NULL REFERENCES services(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    subject TEXT NOT NULL,
+    issuer TEXT NOT NULL,
+    not_before TIMESTAMP WITH TIME ZONE NOT NULL,
+    not_after TIMESTAMP WITH TIME ZONE NOT NULL,
+    checked_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (service_id, position)
+);
+
+ALTER TABLE services
+    ADD COLUMN IF NOT EXISTS certificate_warning_days INTEGER NOT NULL DEFAULT 14
+        CHECK (certificate_warning_days >= 0);
+
+ALTER TABLE incidents
+    ADD COLUMN IF NOT EXISTS source VARCHAR(32);
+
+CREATE INDEX IF NOT EXISTS incidents_open_source
+    ON incidents (service_id, source) WHERE end_time IS NULL;
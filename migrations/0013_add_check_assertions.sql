@@ -0,0 +1,3 @@
+ALTER TABLE services
+    ADD COLUMN IF NOT EXISTS check_assertions JSONB NOT NULL DEFAULT '[]'
+        CHECK (jsonb_typeof(check_assertions) = 'array');
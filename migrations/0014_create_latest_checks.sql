@@ -0,0 +1,35 @@
+CREATE TABLE IF NOT EXISTS latest_checks (
+    service_id VARCHAR(255) NOT NULL REFERENCES services(id) ON DELETE CASCADE,
+    region VARCHAR(64) NOT NULL,
+    checked_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    response_ms INTEGER,
+    status VARCHAR(16) NOT NULL,
+    error TEXT,
+    PRIMARY KEY (service_id, region)
+);
+
+INSERT INTO latest_checks (service_id, region, checked_at, response_ms, status, error)
+SELECT DISTINCT ON (service_id, region) service_id, region, checked_at, response_ms, status, error
+FROM check_results
+ORDER BY service_id, region, checked_at DESC
+ON CONFLICT (service_id, region) DO NOTHING;
+
+CREATE OR REPLACE FUNCTION record_latest_check() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO latest_checks (service_id, region, checked_at, response_ms, status, error)
+    VALUES (NEW.service_id, NEW.region, NEW.checked_at, NEW.response_ms, NEW.status, NEW.error)
+    ON CONFLICT (service_id, region) DO UPDATE SET
+        checked_at = EXCLUDED.checked_at,
+        response_ms = EXCLUDED.response_ms,
+        status = EXCLUDED.status,
+        error = EXCLUDED.error
+    WHERE latest_checks.checked_at <= EXCLUDED.checked_at;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS check_results_latest ON check_results;
+CREATE TRIGGER check_results_latest
+    AFTER INSERT ON check_results
+    FOR EACH ROW
+    EXECUTE FUNCTION record_latest_check();
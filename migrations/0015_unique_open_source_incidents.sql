@@ -0,0 +1,10 @@
+UPDATE incidents i SET end_time = NOW()
+WHERE i.end_time IS NULL AND i.source IS NOT NULL AND EXISTS (
+    SELECT 1 FROM incidents o
+    WHERE o.service_id = i.service_id AND o.source = i.source AND o.end_time IS NULL AND o.id < i.id
+);
+
+DROP INDEX IF EXISTS incidents_open_source;
+
+CREATE UNIQUE INDEX IF NOT EXISTS incidents_open_source
+    ON incidents (service_id, source) WHERE end_time IS NULL AND source IS NOT NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE services
+    ADD COLUMN IF NOT EXISTS check_greeting BOOLEAN NOT NULL DEFAULT false;
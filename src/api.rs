@@ -0,0 +1,183 @@
+use actix_web::{web, HttpResponse, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    AppState,
+    load_overview,
+    snapshot::{RETRY_AFTER_SECS, unavailable},
+    history::{HistoryQuery, IncidentPage, load_history},
+    status::{
+        GroupSummary, IncidentSummary, MaintenanceSummary, OverallStatus, ServiceDetail, ServiceSummary, Uptimes, build_service_detail, services_down
+    }
+};
+
+
+#[derive(Debug, Serialize)]
+pub struct StatusResponse {
+    pub status: OverallStatus,
+    pub uptime: Uptimes,
+    pub service_count: usize,
+    pub services_down: usize,
+    pub groups: Vec<GroupSummary>,
+    pub incidents: Vec<IncidentSummary>,
+    pub maintenances: Vec<MaintenanceSummary>,
+    pub stale: bool,
+    pub stale_since: Option<DateTime<Utc>>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServicesResponse {
+    pub services: Vec<ServiceSummary>,
+    pub groups: Vec<GroupSummary>,
+    pub stale: bool,
+    pub stale_since: Option<DateTime<Utc>>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServiceDetailResponse {
+    #[serde(flatten)]
+    pub service: ServiceDetail,
+    pub stale: bool,
+    pub stale_since: Option<DateTime<Utc>>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IncidentsResponse {
+    #[serde(flatten)]
+    pub page: IncidentPage,
+    pub generated_at: DateTime<Utc>,
+}
+
+fn json_response(body: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .insert_header(("Cache-Control", "public, max-age=30"))
+        .body(body)
+}
+
+fn stale_json_response(body: String) -> HttpResponse {
+    HttpResponse::ServiceUnavailable()
+        .content_type("application/json")
+        .insert_header(("Cache-Control", "no-store"))
+        .insert_header(("Retry-After", RETRY_AFTER_SECS))
+        .body(body)
+}
+
+fn serialize<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| {
+        eprintln!("Serialization error: {}", e);
+        actix_web::error::ErrorInternalServerError("Serialization error")
+    })
+}
+
+pub async fn status_api(data: web::Data<AppState>) -> Result<HttpResponse> {
+    if let Some(cached_response) = data.api_cache.get("status").await {
+        return Ok(json_response(cached_response));
+    }
+
+    let overview = load_overview(&data).await?;
+
+    let response = StatusResponse {
+        status: overview.status,
+        uptime: overview.uptime,
+        service_count: overview.services.len(),
+        services_down: services_down(&overview.services),
+        groups: overview.groups,
+        incidents: overview.incidents,
+        maintenances: overview.maintenances,
+        stale: overview.stale_since.is_some(),
+        stale_since: overview.stale_since,
+        generated_at: Utc::now(),
+    };
+
+    let body = serialize(&response)?;
+    if response.stale {
+        return Ok(stale_json_response(body));
+    }
+    data.api_cache.insert("status".to_string(), body.clone()).await;
+
+    Ok(json_response(body))
+}
+
+pub async fn services_api(data: web::Data<AppState>) -> Result<HttpResponse> {
+    if let Some(cached_response) = data.api_cache.get("services").await {
+        return Ok(json_response(cached_response));
+    }
+
+    let overview = load_overview(&data).await?;
+
+    let response = ServicesResponse {
+        services: overview.services,
+        groups: overview.groups,
+        stale: overview.stale_since.is_some(),
+        stale_since: overview.stale_since,
+        generated_at: Utc::now(),
+    };
+
+    let body = serialize(&response)?;
+    if response.stale {
+        return Ok(stale_json_response(body));
+    }
+    data.api_cache.insert("services".to_string(), body.clone()).await;
+
+    Ok(json_response(body))
+}
+
+pub async fn service_detail_api(path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse> {
+    let service_id = path.into_inner();
+    let cache_key = format!("service:{}", service_id);
+
+    if let Some(cached_response) = data.api_cache.get(&cache_key).await {
+        return Ok(json_response(cached_response));
+    }
+
+    let loaded = data.snapshots.load(&data.db).await.ok_or_else(unavailable)?;
+    let service = match loaded.snapshot.services.iter().find(|s| s.id == service_id) {
+        Some(service) => service,
+        None => return Ok(HttpResponse::NotFound().finish())
+    };
+
+    let response = ServiceDetailResponse {
+        service: build_service_detail(service, &loaded.snapshot.incidents),
+        stale: loaded.stale,
+        stale_since: loaded.stale_since(),
+        generated_at: Utc::now(),
+    };
+
+    let body = serialize(&response)?;
+    if response.stale {
+        return Ok(stale_json_response(body));
+    }
+    data.api_cache.insert(cache_key, body.clone()).await;
+
+    Ok(json_response(body))
+}
+
+pub async fn incidents_api(query: web::Query<HistoryQuery>, data: web::Data<AppState>) -> Result<HttpResponse> {
+    let filter = query.to_filter().map_err(actix_web::error::ErrorBadRequest)?;
+    let cache_key = format!("incidents:{}", query.cache_key());
+
+    if let Some(cached_response) = data.api_cache.get(&cache_key).await {
+        return Ok(json_response(cached_response));
+    }
+
+    let page = load_history(&data.db, &query, &filter).await
+        .map_err(|e| {
+            eprintln!("Database error: {}", e);
+            unavailable()
+        })?;
+
+    let response = IncidentsResponse {
+        page,
+        generated_at: Utc::now(),
+    };
+
+    let body = serialize(&response)?;
+    data.api_cache.insert(cache_key, body.clone()).await;
+
+    Ok(json_response(body))
+}
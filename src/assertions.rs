@@ -0,0 +1,243 @@
+use std::fmt;
+
+use regex::Regex;
+use reqwest::{header::HeaderMap, StatusCode};
+use serde::{de, Deserialize, Deserializer};
+use serde_json::Value;
+
+
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    fn is_match(&self, haystack: &str) -> bool {
+        self.0.is_match(haystack)
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "/{}/", self.0.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern)
+            .map(Pattern)
+            .map_err(|e| de::Error::custom(format!("invalid pattern /{}/: {}", pattern, e)))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Assertion {
+    Status { codes: Vec<u16> },
+    BodyContains { value: String },
+    BodyNotContains { value: String },
+    BodyMatches { pattern: Pattern },
+    BodyNotMatches { pattern: Pattern },
+    Header {
+        name: String,
+        equals: Option<String>,
+        pattern: Option<Pattern>,
+    },
+    JsonPointer { pointer: String, equals: Value },
+}
+
+impl Assertion {
+    fn needs_body(&self) -> bool {
+        !matches!(self, Assertion::Status { .. } | Assertion::Header { .. })
+    }
+}
+
+pub fn parse(value: &Value) -> Result<Vec<Assertion>, String> {
+    serde_json::from_value(value.clone())
+        .map_err(|e| format!("Invalid assertions: {}", e))
+}
+
+pub fn needs_body(assertions: &[Assertion]) -> bool {
+    assertions.iter().any(Assertion::needs_body)
+}
+
+fn ensure(passed: bool, message: impl FnOnce() -> String) -> Result<(), String> {
+    if passed { Ok(()) } else { Err(message()) }
+}
+
+fn check_status(assertions: &[Assertion], status: StatusCode) -> Result<(), String> {
+    let mut accepted = assertions.iter()
+        .filter_map(|assertion| match assertion {
+            Assertion::Status { codes } => Some(codes),
+            _ => None
+        })
+        .peekable();
+
+    if accepted.peek().is_none() {
+        return if status.is_success() { Ok(()) } else { Err(format!("Unexpected status {}", status)) };
+    }
+
+    match accepted.find(|codes| !codes.contains(&status.as_u16())) {
+        Some(codes) => Err(format!(
+            "Status {} is not one of {}",
+            status,
+            codes.iter().map(u16::to_string).collect::<Vec<_>>().join(", ")
+        )),
+        None => Ok(())
+    }
+}
+
+fn check_one(assertion: &Assertion, headers: &HeaderMap, body: &str) -> Result<(), String> {
+    match assertion {
+        Assertion::Status { .. } => Ok(()),
+        Assertion::BodyContains { value } => ensure(body.contains(value.as_str()), || format!("Body does not contain '{}'", value)),
+        Assertion::BodyNotContains { value } => ensure(!body.contains(value.as_str()), || format!("Body contains '{}'", value)),
+        Assertion::BodyMatches { pattern } => ensure(pattern.is_match(body), || format!("Body does not match {}", pattern)),
+        Assertion::BodyNotMatches { pattern } => ensure(!pattern.is_match(body), || format!("Body matches {}", pattern)),
+        Assertion::Header { name, equals, pattern } => {
+            let value = headers.get(name.as_str())
+                .ok_or_else(|| format!("Header '{}' is missing", name))?
+                .to_str()
+                .unwrap_or_default();
+
+            if let Some(expected) = equals {
+                ensure(value == expected, || format!("Header '{}' is '{}', expected '{}'", name, value, expected))?;
+            }
+            if let Some(pattern) = pattern {
+                ensure(pattern.is_match(value), || format!("Header '{}' does not match {}", name, pattern))?;
+            }
+            Ok(())
+        },
+        Assertion::JsonPointer { pointer, equals } => {
+            let document: Value = serde_json::from_str(body)
+                .map_err(|e| format!("Body is not valid JSON: {}", e))?;
+
+            match document.pointer(pointer) {
+                Some(value) if value == equals => Ok(()),
+                Some(value) => Err(format!("JSON at '{}' is {}, expected {}", pointer, value, equals)),
+                None => Err(format!("JSON at '{}' is missing", pointer))
+            }
+        },
+    }
+}
+
+pub fn check(assertions: &[Assertion], status: StatusCode, headers: &HeaderMap, body: &str) -> Result<(), String> {
+    check_status(assertions, status)?;
+
+    assertions.iter().try_for_each(|assertion| check_one(assertion, headers, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderValue, CONTENT_TYPE};
+    use serde_json::json;
+
+    const BODY: &str = r#"{"status":"ok","db":{"healthy":false}}"#;
+
+    fn headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json; charset=utf-8"));
+        headers
+    }
+
+    fn run(assertions: Value, status: u16) -> Result<(), String> {
+        let assertions = parse(&assertions)?;
+        check(&assertions, StatusCode::from_u16(status).unwrap(), &headers(), BODY)
+    }
+
+    #[test]
+    fn defaults_to_any_success_status() {
+        assert!(run(json!([]), 204).is_ok());
+        assert_eq!(run(json!([]), 503).unwrap_err(), "Unexpected status 503 Service Unavailable");
+    }
+
+    #[test]
+    fn status_codes_replace_the_default() {
+        assert!(run(json!([{ "type": "status", "codes": [401] }]), 401).is_ok());
+        assert_eq!(
+            run(json!([{ "type": "status", "codes": [200, 204] }]), 202).unwrap_err(),
+            "Status 202 Accepted is not one of 200, 204"
+        );
+    }
+
+    #[test]
+    fn body_contains() {
+        assert!(run(json!([{ "type": "body_contains", "value": "\"ok\"" }]), 200).is_ok());
+        assert_eq!(
+            run(json!([{ "type": "body_contains", "value": "ready" }]), 200).unwrap_err(),
+            "Body does not contain 'ready'"
+        );
+        assert!(run(json!([{ "type": "body_not_contains", "value": "error" }]), 200).is_ok());
+        assert_eq!(
+            run(json!([{ "type": "body_not_contains", "value": "healthy" }]), 200).unwrap_err(),
+            "Body contains 'healthy'"
+        );
+    }
+
+    #[test]
+    fn body_matches() {
+        assert!(run(json!([{ "type": "body_matches", "pattern": "\"status\":\\s*\"ok\"" }]), 200).is_ok());
+        assert_eq!(
+            run(json!([{ "type": "body_matches", "pattern": "^ok$" }]), 200).unwrap_err(),
+            "Body does not match /^ok$/"
+        );
+        assert!(run(json!([{ "type": "body_not_matches", "pattern": "(?i)exception" }]), 200).is_ok());
+        assert_eq!(
+            run(json!([{ "type": "body_not_matches", "pattern": "heal+thy" }]), 200).unwrap_err(),
+            "Body matches /heal+thy/"
+        );
+    }
+
+    #[test]
+    fn header_equals_and_pattern() {
+        assert!(run(json!([{ "type": "header", "name": "content-type", "pattern": "^application/json" }]), 200).is_ok());
+        assert_eq!(
+            run(json!([{ "type": "header", "name": "Content-Type", "equals": "application/json" }]), 200).unwrap_err(),
+            "Header 'Content-Type' is 'application/json; charset=utf-8', expected 'application/json'"
+        );
+        assert_eq!(
+            run(json!([{ "type": "header", "name": "x-request-id" }]), 200).unwrap_err(),
+            "Header 'x-request-id' is missing"
+        );
+    }
+
+    #[test]
+    fn json_pointer() {
+        assert!(run(json!([{ "type": "json_pointer", "pointer": "/status", "equals": "ok" }]), 200).is_ok());
+        assert_eq!(
+            run(json!([{ "type": "json_pointer", "pointer": "/db/healthy", "equals": true }]), 200).unwrap_err(),
+            "JSON at '/db/healthy' is false, expected true"
+        );
+        assert_eq!(
+            run(json!([{ "type": "json_pointer", "pointer": "/version", "equals": 1 }]), 200).unwrap_err(),
+            "JSON at '/version' is missing"
+        );
+    }
+
+    #[test]
+    fn status_is_checked_before_the_body() {
+        let assertions = json!([{ "type": "body_contains", "value": "ok" }]);
+        assert_eq!(run(assertions, 500).unwrap_err(), "Unexpected status 500 Internal Server Error");
+    }
+
+    #[test]
+    fn rejects_invalid_assertions() {
+        let error = run(json!([{ "type": "body_matches", "pattern": "(unclosed" }]), 200).unwrap_err();
+        assert!(error.starts_with("Invalid assertions: invalid pattern /(unclosed/"), "{}", error);
+
+        let error = run(json!([{ "type": "status", "codez": [200] }]), 200).unwrap_err();
+        assert!(error.starts_with("Invalid assertions: unknown field `codez`"), "{}", error);
+
+        assert!(run(json!({ "type": "status", "codes": [200] }), 200).is_err());
+    }
+
+    #[test]
+    fn only_reads_the_body_when_needed() {
+        let header_only = parse(&json!([{ "type": "status", "codes": [200] }, { "type": "header", "name": "server" }])).unwrap();
+        assert!(!needs_body(&header_only));
+
+        let with_body = parse(&json!([{ "type": "json_pointer", "pointer": "/status", "equals": "ok" }])).unwrap();
+        assert!(needs_body(&with_body));
+    }
+}
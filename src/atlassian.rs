@@ -0,0 +1,337 @@
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    AppState,
+    base_url,
+    load_overview,
+    database::IncidentStatus,
+    status::{GroupSummary, IncidentSummary, MaintenanceSummary, OverallStatus, ServiceSummary, StatusOverview}
+};
+
+
+const PAGE_ID: &str = "statuspage";
+const PAGE_NAME: &str = "Status Page";
+
+#[derive(Debug, Serialize)]
+pub struct Page {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub url: String,
+    pub time_zone: &'static str,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PageStatus {
+    pub indicator: &'static str,
+    pub description: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Component {
+    pub id: String,
+    pub name: String,
+    pub status: &'static str,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+    pub position: usize,
+    pub description: Option<String>,
+    pub showcase: bool,
+    pub start_date: Option<String>,
+    pub group_id: Option<String>,
+    pub page_id: &'static str,
+    pub group: bool,
+    pub only_show_if_degraded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IncidentUpdate {
+    pub id: String,
+    pub status: &'static str,
+    pub body: String,
+    pub incident_id: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub display_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Incident {
+    pub id: String,
+    pub name: String,
+    pub status: &'static str,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub monitoring_at: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub impact: &'static str,
+    pub shortlink: String,
+    pub started_at: DateTime<Utc>,
+    pub page_id: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_for: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_until: Option<DateTime<Utc>>,
+    pub incident_updates: Vec<IncidentUpdate>,
+    pub components: Vec<Component>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SummaryResponse {
+    pub page: Page,
+    pub status: PageStatus,
+    pub components: Vec<Component>,
+    pub incidents: Vec<Incident>,
+    pub scheduled_maintenances: Vec<Incident>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusResponse {
+    pub page: Page,
+    pub status: PageStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ComponentsResponse {
+    pub page: Page,
+    pub components: Vec<Component>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IncidentsResponse {
+    pub page: Page,
+    pub incidents: Vec<Incident>,
+}
+
+fn page(url: &str) -> Page {
+    Page {
+        id: PAGE_ID,
+        name: PAGE_NAME,
+        url: url.to_string(),
+        time_zone: "Etc/UTC",
+        updated_at: Utc::now(),
+    }
+}
+
+fn page_status(status: OverallStatus) -> PageStatus {
+    match status {
+        OverallStatus::Up => PageStatus { indicator: "none", description: "All Systems Operational" },
+        OverallStatus::Degraded => PageStatus { indicator: "minor", description: "Minor Service Outage" },
+        OverallStatus::Partial => PageStatus { indicator: "major", description: "Partial System Outage" },
+        OverallStatus::Down => PageStatus { indicator: "critical", description: "Major System Outage" },
+    }
+}
+
+fn component_status(status: OverallStatus) -> &'static str {
+    match status {
+        OverallStatus::Up => "operational",
+        OverallStatus::Degraded => "degraded_performance",
+        OverallStatus::Partial => "partial_outage",
+        OverallStatus::Down => "major_outage",
+    }
+}
+
+fn component(service: &ServiceSummary, position: usize) -> Component {
+    Component {
+        id: service.id.clone(),
+        name: service.name.clone(),
+        status: component_status(service.state),
+        created_at: None,
+        updated_at: Utc::now(),
+        position,
+        description: service.description.clone(),
+        showcase: true,
+        start_date: None,
+        group_id: service.group_id.clone(),
+        page_id: PAGE_ID,
+        group: false,
+        only_show_if_degraded: false,
+        components: None,
+    }
+}
+
+fn group_component(group: &GroupSummary, position: usize) -> Component {
+    Component {
+        id: group.id.clone(),
+        name: group.name.clone(),
+        status: component_status(group.status),
+        created_at: None,
+        updated_at: Utc::now(),
+        position,
+        description: None,
+        showcase: false,
+        start_date: None,
+        group_id: None,
+        page_id: PAGE_ID,
+        group: true,
+        only_show_if_degraded: false,
+        components: Some(group.services.clone()),
+    }
+}
+
+fn components(overview: &StatusOverview) -> Vec<Component> {
+    let services = overview.services.iter()
+        .enumerate()
+        .map(|(i, service)| component(service, i + 1));
+
+    let groups = overview.groups.iter()
+        .enumerate()
+        .map(|(i, group)| group_component(group, overview.services.len() + i + 1));
+
+    services.chain(groups).collect()
+}
+
+fn incident(incident: &IncidentSummary, overview: &StatusOverview, url: &str) -> Incident {
+    let updated_at = incident.updates.last()
+        .map(|update| update.created_at)
+        .unwrap_or(incident.end_time.unwrap_or(incident.start_time));
+    let monitoring_at = incident.updates.iter()
+        .find(|update| update.status == IncidentStatus::Monitoring)
+        .map(|update| update.created_at);
+
+    let affected = overview.services.iter()
+        .enumerate()
+        .filter(|(_, service)| service.id == incident.service_id)
+        .map(|(i, service)| component(service, i + 1))
+        .collect();
+
+    Incident {
+        id: incident.id.to_string(),
+        name: format!("{}: {}", incident.service_name, incident.description),
+        status: incident.status.as_str(),
+        created_at: incident.start_time,
+        updated_at,
+        monitoring_at,
+        resolved_at: incident.end_time,
+        impact: incident.impact.as_str(),
+        shortlink: format!("{}/incidents/{}", url, incident.id),
+        started_at: incident.start_time,
+        page_id: PAGE_ID,
+        scheduled_for: None,
+        scheduled_until: None,
+        incident_updates: incident.updates.iter().rev().map(|update| IncidentUpdate {
+            id: update.id.to_string(),
+            status: update.status.as_str(),
+            body: update.body.clone(),
+            incident_id: incident.id.to_string(),
+            created_at: update.created_at,
+            updated_at: update.created_at,
+            display_at: update.created_at,
+        }).collect(),
+        components: affected,
+    }
+}
+
+fn maintenance(maintenance: &MaintenanceSummary, overview: &StatusOverview, url: &str) -> Incident {
+    let status = if maintenance.in_progress { "in_progress" } else { "scheduled" };
+
+    let affected = overview.services.iter()
+        .enumerate()
+        .filter(|(_, service)| maintenance.service_ids.contains(&service.id))
+        .map(|(i, service)| component(service, i + 1))
+        .collect();
+
+    Incident {
+        id: format!("maintenance-{}", maintenance.id),
+        name: maintenance.description.clone(),
+        status,
+        created_at: maintenance.start_time,
+        updated_at: maintenance.start_time,
+        monitoring_at: None,
+        resolved_at: None,
+        impact: "maintenance",
+        shortlink: url.to_string(),
+        started_at: maintenance.start_time,
+        page_id: PAGE_ID,
+        scheduled_for: Some(maintenance.start_time),
+        scheduled_until: Some(maintenance.end_time),
+        incident_updates: vec![IncidentUpdate {
+            id: format!("maintenance-{}-1", maintenance.id),
+            status,
+            body: maintenance.description.clone(),
+            incident_id: format!("maintenance-{}", maintenance.id),
+            created_at: maintenance.start_time,
+            updated_at: maintenance.start_time,
+            display_at: maintenance.start_time,
+        }],
+        components: affected,
+    }
+}
+
+fn maintenances(overview: &StatusOverview, url: &str) -> Vec<Incident> {
+    overview.maintenances.iter()
+        .map(|m| maintenance(m, overview, url))
+        .collect()
+}
+
+fn incidents(overview: &StatusOverview, url: &str) -> Vec<Incident> {
+    overview.incidents.iter()
+        .map(|i| incident(i, overview, url))
+        .collect()
+}
+
+async fn cached_json<T, F>(req: &HttpRequest, data: &AppState, name: &str, build: F) -> Result<HttpResponse>
+where
+    T: Serialize,
+    F: FnOnce(StatusOverview, &str) -> T,
+{
+    let url = base_url(req);
+    let cache_key = format!("v2:{}:{}", name, url);
+
+    let (body, stale) = match data.api_cache.get(&cache_key).await {
+        Some(cached_response) => (cached_response, false),
+        None => {
+            let overview = load_overview(data).await?;
+            let stale = overview.stale_since.is_some();
+            let body = serde_json::to_string(&build(overview, &url)).map_err(|e| {
+                eprintln!("Serialization error: {}", e);
+                actix_web::error::ErrorInternalServerError("Serialization error")
+            })?;
+            if !stale {
+                data.api_cache.insert(cache_key, body.clone()).await;
+            }
+            (body, stale)
+        }
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .insert_header(("Cache-Control", if stale { "no-store" } else { "public, max-age=30" }))
+        .insert_header(("Access-Control-Allow-Origin", "*"))
+        .body(body))
+}
+
+pub async fn summary_api(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
+    cached_json(&req, &data, "summary", |overview, url| SummaryResponse {
+        page: page(url),
+        status: page_status(overview.status),
+        components: components(&overview),
+        incidents: incidents(&overview, url),
+        scheduled_maintenances: maintenances(&overview, url),
+    }).await
+}
+
+pub async fn status_api(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
+    cached_json(&req, &data, "status", |overview, url| StatusResponse {
+        page: page(url),
+        status: page_status(overview.status),
+    }).await
+}
+
+pub async fn components_api(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
+    cached_json(&req, &data, "components", |overview, url| ComponentsResponse {
+        page: page(url),
+        components: components(&overview),
+    }).await
+}
+
+pub async fn unresolved_incidents_api(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
+    cached_json(&req, &data, "unresolved", |overview, url| IncidentsResponse {
+        page: page(url),
+        incidents: incidents(&overview, url),
+    }).await
+}
@@ -0,0 +1,208 @@
+use actix_web::{web, HttpResponse, Result};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    CachedResponse,
+    escape_xml,
+    load_overview,
+    status::{OverallStatus, Uptimes, service_state, uptimes}
+};
+
+
+const COLOR_BRIGHTGREEN: &str = "#4c1";
+const COLOR_GREEN: &str = "#97ca00";
+const COLOR_YELLOW: &str = "#dfb317";
+const COLOR_ORANGE: &str = "#fe7d37";
+const COLOR_RED: &str = "#e05d44";
+const COLOR_LABEL: &str = "#555";
+const COLOR_UNKNOWN: &str = "#9f9f9f";
+
+#[derive(Debug, Deserialize)]
+pub struct BadgeQuery {
+    pub label: Option<String>,
+}
+
+fn text_width(text: &str) -> u32 {
+    text.chars().map(|c| match c {
+        'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '!' | '|' | '\'' => 3,
+        'f' | 'r' | 't' | 'I' | ' ' | '(' | ')' | '[' | ']' | '-' => 4,
+        'm' | 'w' | 'M' | 'W' | '%' => 10,
+        'A'..='Z' | '0'..='9' => 7,
+        _ => 6,
+    }).sum()
+}
+
+pub fn render_badge(label: &str, message: &str, color: &str) -> String {
+    let label_width = text_width(label) + 10;
+    let message_width = text_width(message) + 10;
+    let width = label_width + message_width;
+    let (label, message) = (escape_xml(label), escape_xml(message));
+
+    format!(
+        concat!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">"##,
+            r##"<title>{label}: {message}</title>"##,
+            r##"<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>"##,
+            r##"<clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>"##,
+            r##"<g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="{label_color}"/>"##,
+            r##"<rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/>"##,
+            r##"<rect width="{width}" height="20" fill="url(#s)"/></g>"##,
+            r##"<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">"##,
+            r##"<text x="{label_x}" y="15" fill="#010101" fill-opacity=".3">{label}</text><text x="{label_x}" y="14">{label}</text>"##,
+            r##"<text x="{message_x}" y="15" fill="#010101" fill-opacity=".3">{message}</text><text x="{message_x}" y="14">{message}</text>"##,
+            r##"</g></svg>"##
+        ),
+        width = width,
+        label_width = label_width,
+        message_width = message_width,
+        label_color = COLOR_LABEL,
+        color = color,
+        label = label,
+        message = message,
+        label_x = label_width as f64 / 2.0,
+        message_x = label_width as f64 + message_width as f64 / 2.0,
+    )
+}
+
+pub fn uptime_color(uptime: f64) -> &'static str {
+    if uptime >= 99.9 {
+        COLOR_BRIGHTGREEN
+    } else if uptime >= 99.0 {
+        COLOR_GREEN
+    } else if uptime >= 95.0 {
+        COLOR_YELLOW
+    } else if uptime >= 90.0 {
+        COLOR_ORANGE
+    } else {
+        COLOR_RED
+    }
+}
+
+fn uptime_for_window(uptime: &Uptimes, window: &str) -> Option<f64> {
+    match window {
+        "24h" => Some(uptime.last_24h),
+        "7d" => Some(uptime.last_7d),
+        "30d" => Some(uptime.last_30d),
+        "90d" => Some(uptime.last_90d),
+        _ => None
+    }
+}
+
+fn svg_response(cached: CachedResponse) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(cached.content_type.as_str())
+        .insert_header(("Cache-Control", "public, max-age=60"))
+        .body(cached.content)
+}
+
+fn unknown_badge(label: &str) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("image/svg+xml")
+        .insert_header(("Cache-Control", "no-store"))
+        .body(render_badge(label, "unknown", COLOR_UNKNOWN))
+}
+
+fn stale_badge(label: &str, message: &str, color: &str) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("image/svg+xml")
+        .insert_header(("Cache-Control", "no-store"))
+        .body(render_badge(label, &format!("{} (stale)", message), color))
+}
+
+async fn cache_badge(data: &AppState, cache_key: String, label: &str, message: &str, color: &str, stale: bool) -> HttpResponse {
+    if stale {
+        return stale_badge(label, message, color);
+    }
+
+    let cached = CachedResponse {
+        content: render_badge(label, message, color).into_bytes(),
+        content_type: "image/svg+xml".to_string(),
+    };
+    data.badge_cache.insert(cache_key, cached.clone()).await;
+
+    svg_response(cached)
+}
+
+pub async fn overall_badge(query: web::Query<BadgeQuery>, data: web::Data<AppState>) -> Result<HttpResponse> {
+    let label = query.label.as_deref().unwrap_or("status");
+    let cache_key = format!("overall:{}", label);
+
+    if let Some(cached) = data.badge_cache.get(&cache_key).await {
+        return Ok(svg_response(cached));
+    }
+
+    let overview = match load_overview(&data).await {
+        Ok(overview) => overview,
+        Err(_) => return Ok(unknown_badge(label))
+    };
+    let (message, color) = match overview.status {
+        OverallStatus::Up => ("operational", COLOR_BRIGHTGREEN),
+        OverallStatus::Degraded => ("degraded", COLOR_YELLOW),
+        OverallStatus::Partial => ("partial outage", COLOR_ORANGE),
+        OverallStatus::Down => ("major outage", COLOR_RED),
+    };
+
+    Ok(cache_badge(&data, cache_key, label, message, color, overview.stale_since.is_some()).await)
+}
+
+pub async fn status_badge(path: web::Path<String>, query: web::Query<BadgeQuery>, data: web::Data<AppState>) -> Result<HttpResponse> {
+    let service_id = path.into_inner();
+    let cache_key = format!("status:{}:{}", service_id, query.label.as_deref().unwrap_or_default());
+
+    if let Some(cached) = data.badge_cache.get(&cache_key).await {
+        return Ok(svg_response(cached));
+    }
+
+    let loaded = match data.snapshots.load(&data.db).await {
+        Some(loaded) => loaded,
+        None => return Ok(unknown_badge(query.label.as_deref().unwrap_or(&service_id)))
+    };
+    let service = match loaded.snapshot.services.iter().find(|s| s.id == service_id) {
+        Some(service) => service,
+        None => return Ok(HttpResponse::NotFound().finish())
+    };
+
+    let label = query.label.as_deref().unwrap_or(&service.name);
+    let (message, color) = match service_state(service, &loaded.snapshot.incidents) {
+        OverallStatus::Up => ("up", COLOR_BRIGHTGREEN),
+        OverallStatus::Degraded => ("degraded", COLOR_YELLOW),
+        OverallStatus::Partial => ("partial outage", COLOR_ORANGE),
+        OverallStatus::Down => ("down", COLOR_RED),
+    };
+
+    Ok(cache_badge(&data, cache_key, label, message, color, loaded.stale).await)
+}
+
+pub async fn uptime_badge(path: web::Path<(String, String)>, query: web::Query<BadgeQuery>, data: web::Data<AppState>) -> Result<HttpResponse> {
+    let (service_id, window) = path.into_inner();
+    let window = match window.strip_suffix(".svg") {
+        Some(window) => window.to_string(),
+        None => return Ok(HttpResponse::NotFound().finish())
+    };
+    let cache_key = format!("uptime:{}:{}:{}", service_id, window, query.label.as_deref().unwrap_or_default());
+
+    if let Some(cached) = data.badge_cache.get(&cache_key).await {
+        return Ok(svg_response(cached));
+    }
+
+    let loaded = match data.snapshots.load(&data.db).await {
+        Some(loaded) => loaded,
+        None => return Ok(unknown_badge(query.label.as_deref().unwrap_or(&service_id)))
+    };
+    let service = match loaded.snapshot.services.iter().find(|s| s.id == service_id) {
+        Some(service) => service,
+        None => return Ok(HttpResponse::NotFound().finish())
+    };
+
+    let uptime = match uptime_for_window(&uptimes(&service.checks), &window) {
+        Some(uptime) => uptime,
+        None => return Ok(HttpResponse::NotFound().finish())
+    };
+
+    let default_label = format!("uptime {}", window);
+    let label = query.label.as_deref().unwrap_or(&default_label);
+    let message = format!("{:.2}%", uptime);
+
+    Ok(cache_badge(&data, cache_key, label, &message, uptime_color(uptime), loaded.stale).await)
+}
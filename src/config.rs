@@ -1,13 +1,23 @@
 use std::{
     env,
-    error::Error
+    error::Error,
+    time::Duration
 };
-use clap::Parser;
+use chrono::{DateTime, Utc};
+use clap::{ArgAction, Parser, Subcommand};
+
+use crate::{monitor::MonitorConfig, outages::OutagePolicy, retention::RetentionPolicy};
 
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, disable_help_flag = true)]
 pub struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(long, action = ArgAction::Help, help = "Print help")]
+    help: Option<bool>,
+
     #[arg(short = 'h', long, default_value = "127.0.0.1")]
     host: String,
 
@@ -17,24 +27,88 @@ pub struct Args {
     #[arg(short = 'w', long, default_value = "16")]
     workers: u16,
 
-    #[arg(long, default_value = "localhost")]
+    #[arg(long, global = true, default_value = "localhost")]
     database_host: String,
 
-    #[arg(long, default_value = "5432")]
+    #[arg(long, global = true, default_value = "5432")]
     database_port: u16,
 
-    #[arg(long, default_value = "postgres")]
+    #[arg(long, global = true, default_value = "postgres")]
     database_name: String,
 
-    #[arg(long, default_value = "postgres")]
+    #[arg(long, global = true, default_value = "postgres")]
     database_user: String,
 
-    #[arg(long)]
+    #[arg(long, global = true)]
     database_password: Option<String>,
+
+    #[arg(long, help = "Check services from this process instead of relying on an external monitor")]
+    monitor: bool,
+
+    #[arg(long, default_value = "60", help = "Seconds between checks when monitoring")]
+    check_interval: u64,
+
+    #[arg(long, default_value = "10", help = "Seconds before a check times out")]
+    check_timeout: u64,
+
+    #[arg(long, default_value = "default", help = "Region recorded with checks made by this process")]
+    monitor_region: String,
+
+    #[arg(long, default_value = "0", help = "Consecutive failed checks before an incident is opened automatically; 0 disables automatic incidents")]
+    incident_threshold: u32,
+
+    #[arg(long, help = "Leave automatic incidents open until they are resolved by hand")]
+    manual_resolution: bool,
+
+    #[arg(long, global = true, default_value = "90", help = "Days of raw check results to keep")]
+    raw_retention_days: u32,
+
+    #[arg(long, global = true, default_value = "13", help = "Months of hourly and daily rollups to keep")]
+    rollup_retention_months: u32,
+
+    #[arg(long, global = true, help = "Days to keep resolved incidents; kept forever when unset")]
+    incident_retention_days: Option<u32>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    #[command(about = "Apply pending database migrations and exit")]
+    Migrate {
+        #[arg(long, help = "List migrations and whether they have been applied instead of running them")]
+        status: bool,
+    },
+    #[command(about = "Post a status update to an incident and exit")]
+    UpdateIncident {
+        #[arg(help = "ID of the incident to update")]
+        incident: i32,
+        #[arg(long, value_parser = ["investigating", "identified", "monitoring", "resolved"], help = "New incident status")]
+        status: String,
+        #[arg(long, help = "Update message shown on the status page")]
+        message: String,
+        #[arg(long, value_parser = ["none", "minor", "major", "critical"], help = "Change the impact of the incident")]
+        impact: Option<String>,
+    },
+    #[command(about = "Schedule a maintenance window and exit")]
+    ScheduleMaintenance {
+        #[arg(long, help = "Start of the window, e.g. 2024-06-01T22:00:00Z")]
+        start: DateTime<Utc>,
+        #[arg(long, help = "End of the window, e.g. 2024-06-01T23:30:00Z")]
+        end: DateTime<Utc>,
+        #[arg(long, help = "Description shown on the status page")]
+        description: String,
+        #[arg(long = "service", required = true, help = "ID of an affected service; repeat for several services")]
+        services: Vec<String>,
+    },
+    #[command(about = "Remove data older than the retention policy and exit")]
+    Prune {
+        #[arg(long, help = "Report what would be removed without deleting anything")]
+        dry_run: bool,
+    },
 }
 
 #[derive(Clone)]
 pub struct Config {
+    pub command: Option<Command>,
     pub host: String,
     pub port: u16,
     pub workers: u16,
@@ -43,6 +117,9 @@ pub struct Config {
     pub database_name: String,
     pub database_user: String,
     pub database_password: String,
+    pub retention: RetentionPolicy,
+    pub monitor: Option<MonitorConfig>,
+    pub outages: Option<OutagePolicy>,
 }
 
 pub fn get_config() -> Result<Config, Box<dyn Error>> {
@@ -102,7 +179,78 @@ pub fn get_config() -> Result<Config, Box<dyn Error>> {
         env::var("DATABASE_PASSWORD").ok().or(args.database_password)
     }.expect("DATABASE_PASSWORD must be provided either via environment variable or command line argument");
 
+    let raw_retention_days = if env::args().any(|arg| arg == "--raw-retention-days") {
+        args.raw_retention_days
+    } else {
+        env::var("RAW_RETENTION_DAYS")
+            .map(|d| d.parse::<u32>().expect("RAW_RETENTION_DAYS must be a valid number"))
+            .unwrap_or(90)
+    };
+
+    let rollup_retention_months = if env::args().any(|arg| arg == "--rollup-retention-months") {
+        args.rollup_retention_months
+    } else {
+        env::var("ROLLUP_RETENTION_MONTHS")
+            .map(|m| m.parse::<u32>().expect("ROLLUP_RETENTION_MONTHS must be a valid number"))
+            .unwrap_or(13)
+    };
+
+    if raw_retention_days < 1 {
+        return Err("Raw retention must be at least 1 day so rollups can be refreshed".into());
+    }
+
+    if rollup_retention_months < 3 {
+        return Err("Rollup retention must be at least 3 months to cover the 90-day history".into());
+    }
+
+    let incident_retention_days = if env::args().any(|arg| arg == "--incident-retention-days") {
+        args.incident_retention_days
+    } else {
+        env::var("INCIDENT_RETENTION_DAYS").ok()
+            .map(|d| d.parse::<u32>().expect("INCIDENT_RETENTION_DAYS must be a valid number"))
+    };
+
+    let monitor_enabled = args.monitor || env::var("MONITOR").is_ok_and(|m| m == "1" || m.eq_ignore_ascii_case("true"));
+
+    let check_interval = if env::args().any(|arg| arg == "--check-interval") {
+        args.check_interval
+    } else {
+        env::var("CHECK_INTERVAL")
+            .map(|i| i.parse::<u64>().expect("CHECK_INTERVAL must be a valid number of seconds"))
+            .unwrap_or(60)
+    };
+
+    let check_timeout = if env::args().any(|arg| arg == "--check-timeout") {
+        args.check_timeout
+    } else {
+        env::var("CHECK_TIMEOUT")
+            .map(|t| t.parse::<u64>().expect("CHECK_TIMEOUT must be a valid number of seconds"))
+            .unwrap_or(10)
+    };
+
+    if monitor_enabled && (check_interval == 0 || check_timeout == 0 || check_timeout > check_interval) {
+        return Err("Check timeout must be positive and no longer than the check interval".into());
+    }
+
+    let monitor_region = if env::args().any(|arg| arg == "--monitor-region") {
+        args.monitor_region
+    } else {
+        env::var("MONITOR_REGION").unwrap_or(String::from("default"))
+    };
+
+    let incident_threshold = if env::args().any(|arg| arg == "--incident-threshold") {
+        args.incident_threshold
+    } else {
+        env::var("INCIDENT_THRESHOLD")
+            .map(|t| t.parse::<u32>().expect("INCIDENT_THRESHOLD must be a valid number"))
+            .unwrap_or(0)
+    };
+
+    let manual_resolution = args.manual_resolution
+        || env::var("MANUAL_RESOLUTION").is_ok_and(|m| m == "1" || m.eq_ignore_ascii_case("true"));
+
     Ok(Config {
+        command: args.command,
         host,
         port,
         workers,
@@ -111,5 +259,19 @@ pub fn get_config() -> Result<Config, Box<dyn Error>> {
         database_name,
         database_user,
         database_password,
+        retention: RetentionPolicy {
+            raw_days: raw_retention_days,
+            rollup_months: rollup_retention_months,
+            incident_days: incident_retention_days,
+        },
+        monitor: monitor_enabled.then(|| MonitorConfig {
+            interval: Duration::from_secs(check_interval),
+            timeout: Duration::from_secs(check_timeout),
+            region: monitor_region,
+        }),
+        outages: (incident_threshold > 0).then_some(OutagePolicy {
+            threshold: incident_threshold,
+            manual_resolution,
+        }),
     })
 }
\ No newline at end of file
@@ -1,53 +1,259 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use std::{
+    collections::HashMap,
     fmt,
+    str::FromStr,
     sync::Arc,
     error::Error,
     io::Error as IoError
 };
 
 use tokio_postgres::NoTls as AsyncNoTls;
-use deadpool_postgres::{Config, Pool, Runtime};
+use deadpool_postgres::{Config, Object, Pool, Runtime, Status};
 use serde::Serialize;
 
 
-pub async fn init_database(pool: &DbPool) -> Result<(), MonitoringError> {
-    let client = pool.pool.get().await
-        .map_err(|e| MonitoringError(e.to_string()))?;
-    
-    client.batch_execute("
-        CREATE TABLE IF NOT EXISTS services (
-            id VARCHAR(255) PRIMARY KEY,
-            name VARCHAR(255) NOT NULL,
-            server_url TEXT NOT NULL,
-            response_times INTEGER[] DEFAULT array[]::INTEGER[],
-            is_online BOOLEAN DEFAULT false
-        );
-
-        CREATE TABLE IF NOT EXISTS incidents (
-            id SERIAL PRIMARY KEY,
-            service_id VARCHAR(255) REFERENCES services(id),
-            service_name VARCHAR(255) NOT NULL,
-            start_time TIMESTAMP WITH TIME ZONE NOT NULL,
-            end_time TIMESTAMP WITH TIME ZONE,
-            description TEXT NOT NULL
-        );
-    ").await.map_err(|e| MonitoringError(e.to_string()))?;
-
-    Ok(())
-}
-
 #[derive(Debug, Clone, Serialize)]
 pub struct Service {
     pub id: String,
     pub name: String,
     pub server_url: String,
-    pub response_times: Vec<i32>,
+    pub description: Option<String>,
+    pub public_url: Option<String>,
+    pub position: i32,
+    pub hidden: bool,
+    pub tags: Vec<String>,
     pub is_online: bool,
+    pub region_quorum: Option<i32>,
+    pub certificate_warning_days: i32,
+    pub certificate_expires_at: Option<DateTime<Utc>>,
+    pub group_id: Option<String>,
+    pub checks: CheckStats,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckType {
+    Http,
+    Tcp,
+    Tls,
+    Banner,
+}
+
+impl FromStr for CheckType {
+    type Err = MonitoringError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "http" => Ok(CheckType::Http),
+            "tcp" => Ok(CheckType::Tcp),
+            "tls" => Ok(CheckType::Tls),
+            "banner" => Ok(CheckType::Banner),
+            _ => Err(MonitoringError(format!("Unknown check type '{}'", value)))
+        }
+    }
+}
+
+impl CheckType {
+    fn name(self) -> &'static str {
+        match self {
+            CheckType::Http => "http",
+            CheckType::Tcp => "tcp",
+            CheckType::Tls => "tls",
+            CheckType::Banner => "banner",
+        }
+    }
+
+    // Resolves the check type against the URL scheme. `http` is the column
+    // default, so `tcp://` and `tls://` URLs imply their own check type.
+    pub fn for_url(self, server_url: &str) -> Result<CheckType, MonitoringError> {
+        let scheme = server_url.split_once("://").map(|(scheme, _)| scheme.to_ascii_lowercase()).unwrap_or_default();
+
+        match (self, scheme.as_str()) {
+            (CheckType::Http, "http" | "https") => Ok(self),
+            (CheckType::Http, "tcp") => Ok(CheckType::Tcp),
+            (CheckType::Http, "tls") => Ok(CheckType::Tls),
+            (CheckType::Tcp, "tcp") => Ok(self),
+            (CheckType::Tls, "tls" | "https") => Ok(self),
+            (CheckType::Banner, scheme) if !matches!(scheme, "" | "http" | "https") => Ok(self),
+            _ => Err(MonitoringError(format!("Check type '{}' does not match server URL '{}'", self.name(), server_url)))
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckTarget {
+    pub service_id: String,
+    pub server_url: String,
+    pub check_type: CheckType,
+    pub greeting: bool,
+    pub send: Option<String>,
+    pub expect: Option<String>,
+    pub assertions: serde_json::Value,
+    pub certificate_warning_days: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Certificate {
+    pub subject: String,
+    pub issuer: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutageState {
+    pub service_id: String,
+    pub name: String,
+    pub confirmed: bool,
+    pub recovered: bool,
+    pub error: Option<String>,
+    pub outage_since: Option<DateTime<Utc>>,
+    pub last_reported: Option<DateTime<Utc>>,
+    pub open: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceGroup {
+    pub id: String,
+    pub name: String,
+    pub position: i32,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CheckWindow {
+    pub samples: i64,
+    pub up: i64,
+}
+
+impl std::ops::AddAssign for CheckWindow {
+    fn add_assign(&mut self, other: CheckWindow) {
+        self.samples += other.samples;
+        self.up += other.up;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RegionChecks {
+    pub region: String,
+    pub last_24h: CheckWindow,
+    pub last_7d: CheckWindow,
+    pub last_30d: CheckWindow,
+    pub last_90d: CheckWindow,
+    pub latest_up: Option<bool>,
+    pub latest_checked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyChecks {
+    pub date: NaiveDate,
+    pub samples: i64,
+    pub down: i64,
+    pub avg_response_ms: Option<f64>,
+    pub min_response_ms: Option<i32>,
+    pub max_response_ms: Option<i32>,
+    pub p95_response_ms: Option<f64>,
+    pub maintenance: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CheckStats {
+    pub last_24h: CheckWindow,
+    pub last_7d: CheckWindow,
+    pub last_30d: CheckWindow,
+    pub last_90d: CheckWindow,
+    pub daily: Vec<DailyChecks>,
+    pub regions: Vec<RegionChecks>,
+    pub latest_response_ms: Option<i32>,
+    pub latest_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IncidentStatus {
+    Investigating,
+    Identified,
+    Monitoring,
+    Resolved,
+}
+
+impl IncidentStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IncidentStatus::Investigating => "investigating",
+            IncidentStatus::Identified => "identified",
+            IncidentStatus::Monitoring => "monitoring",
+            IncidentStatus::Resolved => "resolved",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            IncidentStatus::Investigating => "Investigating",
+            IncidentStatus::Identified => "Identified",
+            IncidentStatus::Monitoring => "Monitoring",
+            IncidentStatus::Resolved => "Resolved",
+        }
+    }
+}
+
+impl FromStr for IncidentStatus {
+    type Err = MonitoringError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "investigating" => Ok(IncidentStatus::Investigating),
+            "identified" => Ok(IncidentStatus::Identified),
+            "monitoring" => Ok(IncidentStatus::Monitoring),
+            "resolved" => Ok(IncidentStatus::Resolved),
+            _ => Err(MonitoringError(format!("Unknown incident status '{}'", value)))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IncidentImpact {
+    None,
+    Minor,
+    Major,
+    Critical,
+}
+
+impl IncidentImpact {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IncidentImpact::None => "none",
+            IncidentImpact::Minor => "minor",
+            IncidentImpact::Major => "major",
+            IncidentImpact::Critical => "critical",
+        }
+    }
+}
+
+impl FromStr for IncidentImpact {
+    type Err = MonitoringError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(IncidentImpact::None),
+            "minor" => Ok(IncidentImpact::Minor),
+            "major" => Ok(IncidentImpact::Major),
+            "critical" => Ok(IncidentImpact::Critical),
+            _ => Err(MonitoringError(format!("Unknown incident impact '{}'", value)))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IncidentUpdate {
+    pub id: i32,
+    pub incident_id: i32,
+    pub status: IncidentStatus,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct Incident {
     pub id: i32,
     pub service_id: String,
@@ -55,6 +261,26 @@ pub struct Incident {
     pub start_time: DateTime<Utc>,
     pub end_time: Option<DateTime<Utc>>,
     pub description: String,
+    pub impact: IncidentImpact,
+    pub updates: Vec<IncidentUpdate>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IncidentFilter {
+    pub service_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Maintenance {
+    pub id: i32,
+    pub description: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub service_ids: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -86,6 +312,88 @@ impl From<String> for MonitoringError {
     }
 }
 
+fn incident_from_row(row: &tokio_postgres::Row) -> Result<Incident, MonitoringError> {
+    let impact: String = row.get(6);
+
+    Ok(Incident {
+        id: row.get(0),
+        service_id: row.get(1),
+        service_name: row.get(2),
+        start_time: row.get(3),
+        end_time: row.get(4),
+        description: row.get(5),
+        impact: impact.parse()?,
+        updates: Vec::new(),
+    })
+}
+
+fn incident_update_from_row(row: &tokio_postgres::Row) -> Result<IncidentUpdate, MonitoringError> {
+    let status: String = row.get(2);
+
+    Ok(IncidentUpdate {
+        id: row.get(0),
+        incident_id: row.get(1),
+        status: status.parse()?,
+        body: row.get(3),
+        created_at: row.get(4),
+    })
+}
+
+async fn attach_updates(client: &Object, incidents: &mut [Incident]) -> Result<(), MonitoringError> {
+    let ids: Vec<i32> = incidents.iter().map(|incident| incident.id).collect();
+    let rows = client.query(
+        "SELECT id, incident_id, status, body, created_at FROM incident_updates \
+        WHERE incident_id = ANY($1) ORDER BY created_at, id",
+        &[&ids]
+    ).await.map_err(|e| MonitoringError(e.to_string()))?;
+
+    let mut updates: HashMap<i32, Vec<IncidentUpdate>> = HashMap::new();
+    for row in &rows {
+        let update = incident_update_from_row(row)?;
+        updates.entry(update.incident_id).or_default().push(update);
+    }
+
+    for incident in incidents {
+        incident.updates = updates.remove(&incident.id).unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+const MAINTENANCE_PERIODS: &str = "
+    SELECT ms.service_id, range_agg(tstzrange(m.start_time, m.end_time)) AS periods
+    FROM maintenance_services ms
+    JOIN maintenances m ON m.id = ms.maintenance_id
+    GROUP BY ms.service_id";
+
+const CHECKS_WITH_MAINTENANCE: &str = "
+    SELECT c.service_id, c.region, c.checked_at, c.status, c.response_ms,
+           COALESCE(p.periods @> c.checked_at, false) AS in_maintenance
+    FROM check_results c
+    LEFT JOIN maintenance_periods p ON p.service_id = c.service_id";
+
+const ROLLUP_COLUMNS: &str = "
+    COUNT(*) FILTER (WHERE NOT in_maintenance),
+    COUNT(*) FILTER (WHERE NOT in_maintenance AND status <> 'up'),
+    COUNT(*) FILTER (WHERE in_maintenance),
+    (AVG(response_ms) FILTER (WHERE NOT in_maintenance AND status = 'up'))::FLOAT8,
+    MIN(response_ms) FILTER (WHERE NOT in_maintenance AND status = 'up'),
+    MAX(response_ms) FILTER (WHERE NOT in_maintenance AND status = 'up'),
+    percentile_cont(0.95) WITHIN GROUP (ORDER BY response_ms) FILTER (WHERE NOT in_maintenance AND status = 'up')";
+
+// Checks can land after their hour has been rolled up (slow regions, retries),
+// so each refresh recomputes this much before the newest hourly rollup.
+const ROLLUP_LOOKBACK: &str = "2 hours";
+
+const ROLLUP_UPDATES: &str = "
+    samples = EXCLUDED.samples,
+    down = EXCLUDED.down,
+    maintenance = EXCLUDED.maintenance,
+    avg_response_ms = EXCLUDED.avg_response_ms,
+    min_response_ms = EXCLUDED.min_response_ms,
+    max_response_ms = EXCLUDED.max_response_ms,
+    p95_response_ms = EXCLUDED.p95_response_ms";
+
 #[derive(Clone)]
 pub struct DbPool {
     pool: Arc<Pool>,
@@ -111,21 +419,472 @@ impl DbPool {
         Ok(Self { pool: Arc::new(pool) })
     }
 
+    pub fn status(&self) -> Status {
+        self.pool.status()
+    }
+
+    pub async fn client(&self) -> Result<Object, MonitoringError> {
+        self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))
+    }
+
     pub async fn list_services(&self) -> Result<Vec<Service>, MonitoringError> {
         let client = self.pool.get().await
             .map_err(|e| MonitoringError(e.to_string()))?;
-        let rows = client.query("SELECT id, name, server_url, response_times, is_online FROM services", &[])
+
+        let window_query = "
+            SELECT service_id, region,
+                   COALESCE(SUM(samples) FILTER (WHERE hour >= NOW() - INTERVAL '24 hours'), 0)::BIGINT,
+                   COALESCE(SUM(samples - down) FILTER (WHERE hour >= NOW() - INTERVAL '24 hours'), 0)::BIGINT,
+                   COALESCE(SUM(samples) FILTER (WHERE hour >= NOW() - INTERVAL '7 days'), 0)::BIGINT,
+                   COALESCE(SUM(samples - down) FILTER (WHERE hour >= NOW() - INTERVAL '7 days'), 0)::BIGINT,
+                   COALESCE(SUM(samples) FILTER (WHERE hour >= NOW() - INTERVAL '30 days'), 0)::BIGINT,
+                   COALESCE(SUM(samples - down) FILTER (WHERE hour >= NOW() - INTERVAL '30 days'), 0)::BIGINT,
+                   SUM(samples)::BIGINT,
+                   SUM(samples - down)::BIGINT
+            FROM check_rollups_hourly
+            WHERE hour >= NOW() - INTERVAL '90 days'
+            GROUP BY service_id, region
+            ORDER BY service_id, region";
+        let daily_query = "
+            SELECT service_id, day, samples, down, avg_response_ms, min_response_ms, max_response_ms, maintenance, p95_response_ms
+            FROM check_rollups_daily
+            WHERE day >= (NOW() AT TIME ZONE 'UTC')::DATE - 89
+            ORDER BY service_id, day";
+        let services_query = "
+            SELECT id, name, server_url, is_online, group_id, description, public_url, position, hidden, tags, region_quorum,
+                   certificate_warning_days,
+                   (SELECT not_after FROM service_certificates c WHERE c.service_id = services.id AND c.position = 0)
+            FROM services
+            WHERE NOT hidden
+            ORDER BY position, name";
+
+        let (rows, window_rows, daily_rows, latest_rows) = tokio::try_join!(
+            client.query(services_query, &[]),
+            client.query(window_query, &[]),
+            client.query(daily_query, &[]),
+            client.query("
+                SELECT service_id, region, response_ms, status, checked_at, error FROM latest_checks ORDER BY service_id, region", &[])
+        ).map_err(|e| MonitoringError(e.to_string()))?;
+
+        let mut stats: HashMap<String, CheckStats> = HashMap::new();
+
+        // Regions come from the latest checks so a region that has not been
+        // rolled up yet still counts towards the quorum.
+        let mut latest_checks: HashMap<String, DateTime<Utc>> = HashMap::new();
+        for row in &latest_rows {
+            let service_id: String = row.get(0);
+            let status: String = row.get(3);
+            let checked_at: DateTime<Utc> = row.get(4);
+            let entry = stats.entry(service_id.clone()).or_default();
+
+            entry.regions.push(RegionChecks {
+                region: row.get(1),
+                latest_up: Some(status == "up"),
+                latest_checked_at: Some(checked_at),
+                ..Default::default()
+            });
+            if latest_checks.get(&service_id).is_none_or(|latest| checked_at > *latest) {
+                entry.latest_response_ms = row.get(2);
+                entry.latest_error = if status == "up" { None } else { row.get(5) };
+                latest_checks.insert(service_id, checked_at);
+            }
+        }
+
+        for row in &window_rows {
+            let window = |samples: usize| CheckWindow { samples: row.get(samples), up: row.get(samples + 1) };
+            let region_name: String = row.get(1);
+            let entry = stats.entry(row.get(0)).or_default();
+            let region = match entry.regions.iter().position(|r| r.region == region_name) {
+                Some(index) => &mut entry.regions[index],
+                None => {
+                    entry.regions.push(RegionChecks { region: region_name, ..Default::default() });
+                    entry.regions.last_mut().unwrap()
+                }
+            };
+            region.last_24h = window(2);
+            region.last_7d = window(4);
+            region.last_30d = window(6);
+            region.last_90d = window(8);
+
+            entry.last_24h += region.last_24h;
+            entry.last_7d += region.last_7d;
+            entry.last_30d += region.last_30d;
+            entry.last_90d += region.last_90d;
+        }
+
+        for row in &daily_rows {
+            stats.entry(row.get(0)).or_default().daily.push(DailyChecks {
+                date: row.get(1),
+                samples: row.get(2),
+                down: row.get(3),
+                avg_response_ms: row.get(4),
+                min_response_ms: row.get(5),
+                max_response_ms: row.get(6),
+                maintenance: row.get(7),
+                p95_response_ms: row.get(8),
+            });
+        }
+
+        let services = rows.iter().map(|row| {
+            let id: String = row.get(0);
+            Service {
+                checks: stats.remove(&id).unwrap_or_default(),
+                id,
+                name: row.get(1),
+                server_url: row.get(2),
+                is_online: row.get(3),
+                group_id: row.get(4),
+                description: row.get(5),
+                public_url: row.get(6),
+                position: row.get(7),
+                hidden: row.get(8),
+                tags: row.get(9),
+                region_quorum: row.get(10),
+                certificate_warning_days: row.get(11),
+                certificate_expires_at: row.get(12),
+            }
+        }).collect();
+
+        Ok(services)
+    }
+
+    pub async fn refresh_rollups(&self, since: Option<DateTime<Utc>>, percentiles: bool) -> Result<(), MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+
+        let since: DateTime<Utc> = match since {
+            Some(since) => since,
+            None => client.query_one(
+                &format!("SELECT COALESCE(MAX(hour) - INTERVAL '{}', NOW() - INTERVAL '90 days') FROM check_rollups_hourly", ROLLUP_LOOKBACK), &[]
+            ).await.map_err(|e| MonitoringError(e.to_string()))?.get(0)
+        };
+
+        let hourly_query = format!("
+            WITH maintenance_periods AS ({}),
+            checks AS ({} WHERE c.checked_at >= date_trunc('hour', $1::TIMESTAMPTZ AT TIME ZONE 'UTC') AT TIME ZONE 'UTC')
+            INSERT INTO check_rollups_hourly
+                (service_id, region, hour, samples, down, maintenance, avg_response_ms, min_response_ms, max_response_ms, p95_response_ms)
+            SELECT service_id, region,
+                   date_trunc('hour', checked_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC',
+                   {}
+            FROM checks
+            GROUP BY 1, 2, 3
+            ON CONFLICT (service_id, region, hour) DO UPDATE SET {}", MAINTENANCE_PERIODS, CHECKS_WITH_MAINTENANCE, ROLLUP_COLUMNS, ROLLUP_UPDATES);
+
+        // Daily counts come from the hourly rollups so the current day is not
+        // rescanned from raw checks on every refresh.
+        let daily_query = "
+            INSERT INTO check_rollups_daily
+                (service_id, day, samples, down, maintenance, avg_response_ms, min_response_ms, max_response_ms)
+            SELECT service_id,
+                   (hour AT TIME ZONE 'UTC')::DATE,
+                   SUM(samples)::BIGINT,
+                   SUM(down)::BIGINT,
+                   SUM(maintenance)::BIGINT,
+                   SUM(avg_response_ms * (samples - down)) / NULLIF(SUM(samples - down) FILTER (WHERE avg_response_ms IS NOT NULL), 0),
+                   MIN(min_response_ms),
+                   MAX(max_response_ms)
+            FROM check_rollups_hourly
+            WHERE hour >= date_trunc('day', $1::TIMESTAMPTZ AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+            GROUP BY 1, 2
+            ON CONFLICT (service_id, day) DO UPDATE SET
+                samples = EXCLUDED.samples,
+                down = EXCLUDED.down,
+                maintenance = EXCLUDED.maintenance,
+                avg_response_ms = EXCLUDED.avg_response_ms,
+                min_response_ms = EXCLUDED.min_response_ms,
+                max_response_ms = EXCLUDED.max_response_ms";
+
+        // Percentiles cannot be merged from hourly rows, so they are only
+        // recomputed from raw checks when the caller asks for it.
+        let percentile_query = format!("
+            WITH maintenance_periods AS ({}),
+            checks AS ({} WHERE c.checked_at >= date_trunc('day', $1::TIMESTAMPTZ AT TIME ZONE 'UTC') AT TIME ZONE 'UTC')
+            UPDATE check_rollups_daily d
+            SET p95_response_ms = p.p95
+            FROM (
+                SELECT service_id,
+                       (checked_at AT TIME ZONE 'UTC')::DATE AS day,
+                       percentile_cont(0.95) WITHIN GROUP (ORDER BY response_ms) FILTER (WHERE NOT in_maintenance AND status = 'up') AS p95
+                FROM checks
+                GROUP BY 1, 2
+            ) p
+            WHERE d.service_id = p.service_id AND d.day = p.day", MAINTENANCE_PERIODS, CHECKS_WITH_MAINTENANCE);
+
+        client.execute(&hourly_query, &[&since])
             .await.map_err(|e| MonitoringError(e.to_string()))?;
+        client.execute(daily_query, &[&since])
+            .await.map_err(|e| MonitoringError(e.to_string()))?;
+        if percentiles {
+            client.execute(&percentile_query, &[&since])
+                .await.map_err(|e| MonitoringError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
 
-        let services = rows.iter().map(|row| Service {
+    pub async fn list_check_targets(&self) -> Result<Vec<CheckTarget>, MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+        let rows = client.query("SELECT id, server_url, check_type, check_send, check_expect, certificate_warning_days, check_assertions, check_greeting FROM services ORDER BY id", &[])
+            .await.map_err(|e| MonitoringError(e.to_string()))?;
+
+        Ok(rows.iter().filter_map(|row| {
+            let service_id: String = row.get(0);
+            let server_url: String = row.get(1);
+            let check_type = match row.get::<_, &str>(2).parse().and_then(|check_type: CheckType| check_type.for_url(&server_url)) {
+                Ok(check_type) => check_type,
+                Err(e) => {
+                    eprintln!("Skipping checks for {}: {}", service_id, e);
+                    return None;
+                }
+            };
+
+            Some(CheckTarget {
+                service_id,
+                server_url,
+                check_type,
+                send: row.get(3),
+                expect: row.get(4),
+                certificate_warning_days: row.get(5),
+                assertions: row.get(6),
+                greeting: row.get(7),
+            })
+        }).collect())
+    }
+
+    pub async fn record_check(
+        &self,
+        service_id: &str,
+        region: &str,
+        response_ms: Option<i32>,
+        error: Option<&str>,
+    ) -> Result<(), MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+        let status = if error.is_none() { "up" } else { "down" };
+
+        client.execute("
+            WITH recorded AS (
+                INSERT INTO check_results (service_id, region, checked_at, response_ms, status, error)
+                VALUES ($1, $2, NOW(), $3, $4, $5)
+            )
+            UPDATE services SET is_online = ($4 = 'up') WHERE id = $1",
+            &[&service_id, &region, &response_ms, &status, &error]
+        ).await.map_err(|e| MonitoringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn record_certificates(&self, service_id: &str, chain: &[Certificate]) -> Result<(), MonitoringError> {
+        let mut client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+        let transaction = client.transaction().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+
+        transaction.execute("DELETE FROM service_certificates WHERE service_id = $1", &[&service_id])
+            .await.map_err(|e| MonitoringError(e.to_string()))?;
+        for (position, certificate) in chain.iter().enumerate() {
+            transaction.execute(
+                "INSERT INTO service_certificates (service_id, position, subject, issuer, not_before, not_after) \
+                VALUES ($1, $2, $3, $4, $5, $6)",
+                &[&service_id, &(position as i32), &certificate.subject, &certificate.issuer, &certificate.not_before, &certificate.not_after]
+            ).await.map_err(|e| MonitoringError(e.to_string()))?;
+        }
+
+        transaction.commit().await
+            .map_err(|e| MonitoringError(e.to_string()))
+    }
+
+    pub async fn sync_monitor_incident(
+        &self,
+        service_id: &str,
+        source: &str,
+        problem: Option<(&str, IncidentImpact)>,
+    ) -> Result<(), MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+
+        match problem {
+            Some((description, impact)) => client.execute("
+                INSERT INTO incidents (service_id, service_name, start_time, description, impact, source)
+                SELECT id, name, NOW(), $3::TEXT, $4::VARCHAR, $2::VARCHAR FROM services
+                WHERE id = $1
+                ON CONFLICT (service_id, source) WHERE end_time IS NULL AND source IS NOT NULL DO NOTHING",
+                &[&service_id, &source, &description, &impact.as_str()]
+            ).await,
+            None => client.execute(
+                "UPDATE incidents SET end_time = NOW() WHERE service_id = $1 AND source = $2 AND end_time IS NULL",
+                &[&service_id, &source]
+            ).await
+        }.map_err(|e| MonitoringError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn list_outage_states(
+        &self,
+        threshold: u32,
+        freshness_minutes: i64,
+        source: &str,
+    ) -> Result<Vec<OutageState>, MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+        let rows = client.query("
+            WITH regions AS (
+                SELECT l.service_id, l.region, l.status = 'up' AS latest_up, l.error, l.checked_at,
+                       recent.failures = $1 AS confirmed,
+                       COALESCE(
+                           (
+                               SELECT MAX(c.checked_at) FROM check_results c
+                               WHERE c.service_id = l.service_id AND c.region = l.region AND c.status = 'up'
+                           ),
+                           (
+                               SELECT MIN(c.checked_at) FROM check_results c
+                               WHERE c.service_id = l.service_id AND c.region = l.region
+                           )
+                       ) AS outage_since
+                FROM latest_checks l
+                CROSS JOIN LATERAL (
+                    SELECT COUNT(*) FILTER (WHERE status <> 'up' AND NOT EXISTS (
+                               SELECT 1 FROM maintenance_services ms
+                               JOIN maintenances m ON m.id = ms.maintenance_id
+                               WHERE ms.service_id = l.service_id AND checked_at >= m.start_time AND checked_at < m.end_time
+                           )) AS failures
+                    FROM (
+                        SELECT status, checked_at FROM check_results c
+                        WHERE c.service_id = l.service_id AND c.region = l.region
+                        ORDER BY checked_at DESC
+                        LIMIT $1
+                    ) checks
+                ) recent
+                WHERE l.checked_at >= NOW() - make_interval(mins => $2::INTEGER)
+            ),
+            services_regions AS (
+                SELECT s.id, s.name,
+                       COUNT(r.region) AS fresh,
+                       COUNT(r.region) FILTER (WHERE r.confirmed) AS confirmed,
+                       COUNT(r.region) FILTER (WHERE NOT r.latest_up) AS down,
+                       COALESCE(s.region_quorum::BIGINT, COUNT(r.region)) AS quorum,
+                       MAX(r.outage_since) FILTER (WHERE r.confirmed) AS outage_since,
+                       (array_agg(r.error ORDER BY r.checked_at DESC) FILTER (WHERE NOT r.latest_up))[1] AS error
+                FROM services s
+                LEFT JOIN regions r ON r.service_id = s.id
+                GROUP BY s.id
+            )
+            SELECT id, name,
+                   fresh > 0 AND confirmed >= quorum,
+                   fresh > 0 AND down < quorum,
+                   error,
+                   outage_since,
+                   (SELECT MAX(i.start_time) FROM incidents i WHERE i.service_id = sr.id AND i.source = $3),
+                   EXISTS (SELECT 1 FROM incidents i WHERE i.service_id = sr.id AND i.source = $3 AND i.end_time IS NULL)
+            FROM services_regions sr",
+            &[&(threshold as i64), &(freshness_minutes as i32), &source]
+        ).await.map_err(|e| MonitoringError(e.to_string()))?;
+
+        Ok(rows.iter().map(|row| OutageState {
+            service_id: row.get(0),
+            name: row.get(1),
+            confirmed: row.get(2),
+            recovered: row.get(3),
+            error: row.get(4),
+            outage_since: row.get(5),
+            last_reported: row.get(6),
+            open: row.get(7),
+        }).collect())
+    }
+
+    pub async fn list_service_groups(&self) -> Result<Vec<ServiceGroup>, MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+        let rows = client.query("SELECT id, name, position FROM service_groups ORDER BY position, name", &[])
+            .await.map_err(|e| MonitoringError(e.to_string()))?;
+
+        let groups = rows.iter().map(|row| ServiceGroup {
             id: row.get(0),
             name: row.get(1),
-            server_url: row.get(2),
-            response_times: row.get(3),
-            is_online: row.get(4),
+            position: row.get(2),
         }).collect();
 
-        Ok(services)
+        Ok(groups)
+    }
+
+    pub async fn list_maintenances(&self) -> Result<Vec<Maintenance>, MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+        let rows = client.query("
+            SELECT m.id, m.description, m.start_time, m.end_time,
+                   COALESCE(array_agg(ms.service_id ORDER BY ms.service_id) FILTER (WHERE ms.service_id IS NOT NULL), '{}')
+            FROM maintenances m
+            LEFT JOIN maintenance_services ms ON ms.maintenance_id = m.id
+            WHERE m.end_time > NOW()
+            GROUP BY m.id
+            ORDER BY m.start_time, m.id", &[])
+            .await.map_err(|e| MonitoringError(e.to_string()))?;
+
+        let maintenances = rows.iter().map(|row| Maintenance {
+            id: row.get(0),
+            description: row.get(1),
+            start_time: row.get(2),
+            end_time: row.get(3),
+            service_ids: row.get(4),
+        }).collect();
+
+        Ok(maintenances)
+    }
+
+    pub async fn schedule_maintenance(
+        &self,
+        description: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        service_ids: &[String],
+        rollup_floor: DateTime<Utc>,
+    ) -> Result<Maintenance, MonitoringError> {
+        let mut client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+
+        let known: Vec<String> = client.query("SELECT id FROM services WHERE id = ANY($1)", &[&service_ids])
+            .await.map_err(|e| MonitoringError(e.to_string()))?
+            .iter().map(|row| row.get(0)).collect();
+        let unknown: Vec<&str> = service_ids.iter()
+            .filter(|id| !known.contains(id))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            return Err(MonitoringError(format!("Unknown services: {}", unknown.join(", "))));
+        }
+
+        let transaction = client.transaction().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+
+        let id: i32 = transaction.query_one(
+            "INSERT INTO maintenances (description, start_time, end_time) VALUES ($1, $2, $3) RETURNING id",
+            &[&description, &start_time, &end_time]
+        ).await.map_err(|e| MonitoringError(e.to_string()))?.get(0);
+
+        transaction.execute(
+            "INSERT INTO maintenance_services (maintenance_id, service_id) SELECT $1, unnest($2::VARCHAR[])",
+            &[&id, &service_ids]
+        ).await.map_err(|e| MonitoringError(e.to_string()))?;
+
+        transaction.commit().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+
+        let since = start_time.max(rollup_floor);
+        if since < Utc::now() {
+            self.refresh_rollups(Some(since), true).await?;
+        }
+
+        Ok(Maintenance {
+            id,
+            description: description.to_string(),
+            start_time,
+            end_time,
+            service_ids: service_ids.to_vec(),
+        })
     }
 
     pub async fn list_incidents(&self, include_closed: bool) -> Result<Vec<Incident>, MonitoringError> {
@@ -133,28 +892,111 @@ impl DbPool {
             .map_err(|e| MonitoringError(e.to_string()))?;
         
         let query = if include_closed {
-            "SELECT id, service_id, service_name, start_time, end_time, description FROM incidents"
+            "SELECT id, service_id, service_name, start_time, end_time, description, impact FROM incidents"
         } else {
-            "SELECT id, service_id, service_name, start_time, end_time, description FROM incidents WHERE end_time IS NULL"
+            "SELECT id, service_id, service_name, start_time, end_time, description, impact FROM incidents WHERE end_time IS NULL"
         };
         
         let rows = client.query(query, &[])
             .await.map_err(|e| MonitoringError(e.to_string()))?;
         
-        let incidents = rows.iter().map(|row| {
-            let start_time: DateTime<Utc> = row.get(3);
-            let end_time: Option<DateTime<Utc>> = row.get(4);
-            
-            Incident {
-                id: row.get(0),
-                service_id: row.get(1),
-                service_name: row.get(2),
-                start_time,
-                end_time,
-                description: row.get(5),
-            }
-        }).collect();
+        let mut incidents = rows.iter()
+            .map(incident_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        attach_updates(&client, &mut incidents).await?;
 
         Ok(incidents)
     }
+
+    pub async fn get_incident(&self, id: i32) -> Result<Option<Incident>, MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+
+        let row = client.query_opt(
+            "SELECT id, service_id, service_name, start_time, end_time, description, impact FROM incidents \
+            WHERE id = $1 AND service_id NOT IN (SELECT id FROM services WHERE hidden)",
+            &[&id]
+        ).await.map_err(|e| MonitoringError(e.to_string()))?;
+
+        let mut incident = match row {
+            Some(row) => incident_from_row(&row)?,
+            None => return Ok(None)
+        };
+        attach_updates(&client, std::slice::from_mut(&mut incident)).await?;
+
+        Ok(Some(incident))
+    }
+
+    pub async fn set_incident_impact(&self, incident_id: i32, impact: IncidentImpact) -> Result<(), MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+
+        let updated = client.execute("UPDATE incidents SET impact = $2 WHERE id = $1", &[&incident_id, &impact.as_str()])
+            .await.map_err(|e| MonitoringError(e.to_string()))?;
+        if updated == 0 {
+            return Err(MonitoringError(format!("Incident {} does not exist", incident_id)));
+        }
+
+        Ok(())
+    }
+
+    pub async fn post_incident_update(&self, incident_id: i32, status: IncidentStatus, body: &str) -> Result<IncidentUpdate, MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+
+        let exists = client.query_opt("SELECT 1 FROM incidents WHERE id = $1", &[&incident_id])
+            .await.map_err(|e| MonitoringError(e.to_string()))?;
+        if exists.is_none() {
+            return Err(MonitoringError(format!("Incident {} does not exist", incident_id)));
+        }
+
+        let row = client.query_one(
+            "INSERT INTO incident_updates (incident_id, status, body) VALUES ($1, $2, $3) \
+            RETURNING id, incident_id, status, body, created_at",
+            &[&incident_id, &status.as_str(), &body]
+        ).await.map_err(|e| MonitoringError(e.to_string()))?;
+
+        incident_update_from_row(&row)
+    }
+
+    pub async fn search_incidents(&self, filter: &IncidentFilter) -> Result<(Vec<Incident>, i64), MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+
+        let conditions = "
+            WHERE ($1::VARCHAR IS NULL OR service_id = $1)
+            AND ($2::TIMESTAMPTZ IS NULL OR COALESCE(end_time, NOW()) >= $2)
+            AND ($3::TIMESTAMPTZ IS NULL OR start_time <= $3)
+            AND service_id NOT IN (SELECT id FROM services WHERE hidden)";
+
+        let total: i64 = client.query_one(
+            &format!("SELECT COUNT(*) FROM incidents {}", conditions),
+            &[&filter.service_id, &filter.from, &filter.to]
+        ).await.map_err(|e| MonitoringError(e.to_string()))?.get(0);
+
+        let rows = client.query(
+            &format!(
+                "SELECT id, service_id, service_name, start_time, end_time, description, impact FROM incidents {} \
+                ORDER BY start_time DESC, id DESC LIMIT $4 OFFSET $5",
+                conditions
+            ),
+            &[&filter.service_id, &filter.from, &filter.to, &filter.limit, &filter.offset]
+        ).await.map_err(|e| MonitoringError(e.to_string()))?;
+
+        let mut incidents = rows.iter()
+            .map(incident_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        attach_updates(&client, &mut incidents).await?;
+
+        Ok((incidents, total))
+    }
+
+    pub async fn list_service_names(&self) -> Result<Vec<(String, String)>, MonitoringError> {
+        let client = self.pool.get().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+        let rows = client.query("SELECT id, name FROM services WHERE NOT hidden ORDER BY position, name", &[])
+            .await.map_err(|e| MonitoringError(e.to_string()))?;
+
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
 }
\ No newline at end of file
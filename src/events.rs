@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::Duration
+};
+
+use actix_web::{web, web::Bytes, HttpResponse, Result};
+use futures_util::stream;
+use serde::Serialize;
+use tokio::{sync::broadcast, time::{interval, MissedTickBehavior}};
+
+use crate::{
+    AppState,
+    database::DbPool,
+    snapshot::SnapshotStore,
+    status::{
+        GroupSummary, IncidentSummary, MaintenanceSummary, OverallStatus, ServiceSummary, StatusOverview, Uptimes, build_overview
+    }
+};
+
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OverviewEvent {
+    pub status: OverallStatus,
+    pub uptime: Uptimes,
+    pub groups: Vec<GroupSummary>,
+    pub maintenances: Vec<MaintenanceSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedEvent {
+    pub id: i32,
+    pub service_id: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum StatusEvent {
+    Overview(OverviewEvent),
+    Service(ServiceSummary),
+    IncidentOpened(IncidentSummary),
+    IncidentUpdated(IncidentSummary),
+    IncidentResolved(ResolvedEvent),
+}
+
+impl StatusEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            StatusEvent::Overview(_) => "overview",
+            StatusEvent::Service(_) => "service",
+            StatusEvent::IncidentOpened(_) => "incident_opened",
+            StatusEvent::IncidentUpdated(_) => "incident_updated",
+            StatusEvent::IncidentResolved(_) => "incident_resolved",
+        }
+    }
+
+    fn data(&self) -> serde_json::Result<String> {
+        match self {
+            StatusEvent::Overview(overview) => serde_json::to_string(overview),
+            StatusEvent::Service(service) => serde_json::to_string(service),
+            StatusEvent::IncidentOpened(incident) | StatusEvent::IncidentUpdated(incident) => serde_json::to_string(incident),
+            StatusEvent::IncidentResolved(resolved) => serde_json::to_string(resolved),
+        }
+    }
+
+    fn to_frame(&self) -> Option<Bytes> {
+        match self.data() {
+            Ok(data) => Some(Bytes::from(format!("event: {}\ndata: {}\n\n", self.name(), data))),
+            Err(e) => {
+                eprintln!("Serialization error: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Snapshot {
+    overview: Option<String>,
+    services: HashMap<String, String>,
+    incidents: HashMap<i32, (String, usize)>,
+}
+
+impl Snapshot {
+    fn diff(&mut self, overview: StatusOverview) -> Vec<StatusEvent> {
+        let mut events = Vec::new();
+
+        let summary = OverviewEvent {
+            status: overview.status,
+            uptime: overview.uptime,
+            groups: overview.groups,
+            maintenances: overview.maintenances,
+        };
+        let key = serde_json::to_string(&summary).unwrap_or_default();
+        if self.overview.as_ref() != Some(&key) {
+            self.overview = Some(key);
+            events.push(StatusEvent::Overview(summary));
+        }
+
+        let mut seen = HashMap::with_capacity(overview.services.len());
+        for service in overview.services {
+            let key = serde_json::to_string(&service).unwrap_or_default();
+            if self.services.get(&service.id) != Some(&key) {
+                events.push(StatusEvent::Service(service.clone()));
+            }
+            seen.insert(service.id, key);
+        }
+        self.services = seen;
+
+        let mut open = HashMap::with_capacity(overview.incidents.len());
+        for incident in overview.incidents {
+            open.insert(incident.id, (incident.service_id.clone(), incident.updates.len()));
+            match self.incidents.remove(&incident.id) {
+                None => events.push(StatusEvent::IncidentOpened(incident)),
+                Some((_, updates)) if updates != incident.updates.len() => events.push(StatusEvent::IncidentUpdated(incident)),
+                Some(_) => {}
+            }
+        }
+
+        events.extend(self.incidents.drain().map(|(id, (service_id, _))| {
+            StatusEvent::IncidentResolved(ResolvedEvent { id, service_id })
+        }));
+        self.incidents = open;
+
+        events
+    }
+}
+
+pub struct EventHub {
+    sender: broadcast::Sender<Bytes>,
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Bytes> {
+        self.sender.subscribe()
+    }
+
+    fn publish(&self, event: &StatusEvent) {
+        if let Some(frame) = event.to_frame() {
+            let _ = self.sender.send(frame);
+        }
+    }
+}
+
+pub fn spawn_watcher(db: Arc<DbPool>, hub: Arc<EventHub>, snapshots: Arc<SnapshotStore>) {
+    tokio::spawn(async move {
+        let mut snapshot = Snapshot::default();
+        let mut ticker = interval(POLL_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            if !hub.has_subscribers() {
+                continue;
+            }
+
+            let current = match snapshots.load(&db).await {
+                Some(loaded) if !loaded.stale => loaded.snapshot,
+                _ => continue
+            };
+
+            let overview = build_overview(&current.services, &current.groups, current.incidents.clone(), &current.maintenances);
+            let first_run = snapshot.overview.is_none();
+
+            let events = snapshot.diff(overview);
+            if first_run {
+                continue;
+            }
+
+            for event in &events {
+                hub.publish(event);
+            }
+        }
+    });
+}
+
+pub async fn events_service(data: web::Data<AppState>) -> Result<HttpResponse> {
+    let receiver = data.events.subscribe();
+    let mut keep_alive = interval(KEEP_ALIVE_INTERVAL);
+    keep_alive.reset();
+
+    let opening = Bytes::from(format!("retry: {}\n\n", POLL_INTERVAL.as_millis()));
+
+    let events = stream::unfold(
+        (receiver, keep_alive, Some(opening)),
+        |(mut receiver, mut keep_alive, opening)| async move {
+            if let Some(opening) = opening {
+                return Some((Ok::<_, actix_web::Error>(opening), (receiver, keep_alive, None)));
+            }
+
+            let frame = tokio::select! {
+                message = receiver.recv() => match message {
+                    Ok(frame) => frame,
+                    Err(broadcast::error::RecvError::Lagged(_)) => Bytes::from_static(b"event: resync\ndata: {}\n\n"),
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+                _ = keep_alive.tick() => Bytes::from_static(b": keep-alive\n\n"),
+            };
+            keep_alive.reset();
+
+            Some((Ok(frame), (receiver, keep_alive, None)))
+        }
+    );
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-store"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(events))
+}
@@ -0,0 +1,335 @@
+use actix_web::{web, HttpResponse, Result};
+
+use crate::{
+    AppState,
+    CachedResponse,
+    FAVICONS,
+    status::{OverallStatus, overall_status, service_state}
+};
+
+
+const ICON_SIZE: u32 = 32;
+const SUPERSAMPLE: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FaviconFormat {
+    Ico,
+    Png,
+    Webp,
+    Svg,
+}
+
+impl FaviconFormat {
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "ico" => Some(FaviconFormat::Ico),
+            "png" => Some(FaviconFormat::Png),
+            "webp" => Some(FaviconFormat::Webp),
+            "svg" => Some(FaviconFormat::Svg),
+            _ => None
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            FaviconFormat::Ico => "image/x-icon",
+            FaviconFormat::Png => "image/png",
+            FaviconFormat::Webp => "image/webp",
+            FaviconFormat::Svg => "image/svg+xml",
+        }
+    }
+}
+
+struct FaviconColor {
+    hex: &'static str,
+    rgb: [u8; 3],
+    webp: Option<&'static str>,
+}
+
+fn status_color(status: OverallStatus) -> FaviconColor {
+    match status {
+        OverallStatus::Up => FaviconColor { hex: "#00b894", rgb: [0x00, 0xb8, 0x94], webp: Some("green.webp") },
+        OverallStatus::Degraded => FaviconColor { hex: "#f1c40f", rgb: [0xf1, 0xc4, 0x0f], webp: None },
+        OverallStatus::Partial => FaviconColor { hex: "#f39c12", rgb: [0xf3, 0x9c, 0x12], webp: Some("orange.webp") },
+        OverallStatus::Down => FaviconColor { hex: "#e74c3c", rgb: [0xe7, 0x4c, 0x3c], webp: Some("red.webp") },
+    }
+}
+
+// Shown when no status snapshot can be loaded at all.
+const UNKNOWN_COLOR: FaviconColor = FaviconColor { hex: "#95a5a6", rgb: [0x95, 0xa5, 0xa6], webp: None };
+
+fn render_svg(color: &FaviconColor) -> Vec<u8> {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}"><circle cx="{center}" cy="{center}" r="{radius}" fill="{color}"/></svg>"#,
+        size = ICON_SIZE,
+        center = ICON_SIZE as f64 / 2.0,
+        radius = ICON_SIZE as f64 / 2.0 - 2.0,
+        color = color.hex
+    ).into_bytes()
+}
+
+fn circle_pixels(rgb: [u8; 3]) -> Vec<u8> {
+    let center = ICON_SIZE as f64 / 2.0;
+    let radius = center - 2.0;
+    let samples = SUPERSAMPLE * SUPERSAMPLE;
+    let mut pixels = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let covered = (0..samples).filter(|i| {
+                let sx = x as f64 + (i % SUPERSAMPLE) as f64 / SUPERSAMPLE as f64 + 0.5 / SUPERSAMPLE as f64;
+                let sy = y as f64 + (i / SUPERSAMPLE) as f64 / SUPERSAMPLE as f64 + 0.5 / SUPERSAMPLE as f64;
+                (sx - center).powi(2) + (sy - center).powi(2) <= radius * radius
+            }).count() as u32;
+
+            pixels.extend_from_slice(&rgb);
+            pixels.push((covered * 255 / samples) as u8);
+        }
+    }
+
+    pixels
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn render_png(color: &FaviconColor) -> Vec<u8> {
+    let pixels = circle_pixels(color.rgb);
+    let row_len = (ICON_SIZE * 4) as usize;
+
+    let mut raw = Vec::with_capacity(pixels.len() + ICON_SIZE as usize);
+    for row in pixels.chunks(row_len) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    // A single uncompressed deflate block is enough for a 32x32 icon.
+    let mut zlib = vec![0x78, 0x01, 0x01];
+    zlib.extend_from_slice(&(raw.len() as u16).to_le_bytes());
+    zlib.extend_from_slice(&(!(raw.len() as u16)).to_le_bytes());
+    zlib.extend_from_slice(&raw);
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&ICON_SIZE.to_be_bytes());
+    header.extend_from_slice(&ICON_SIZE.to_be_bytes());
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    png_chunk(&mut png, b"IHDR", &header);
+    png_chunk(&mut png, b"IDAT", &zlib);
+    png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn render_ico(color: &FaviconColor) -> Vec<u8> {
+    let png = render_png(color);
+
+    let mut ico = Vec::with_capacity(22 + png.len());
+    ico.extend_from_slice(&[0, 0, 1, 0, 1, 0]);
+    ico.extend_from_slice(&[ICON_SIZE as u8, ICON_SIZE as u8, 0, 0]);
+    ico.extend_from_slice(&1u16.to_le_bytes());
+    ico.extend_from_slice(&32u16.to_le_bytes());
+    ico.extend_from_slice(&(png.len() as u32).to_le_bytes());
+    ico.extend_from_slice(&22u32.to_le_bytes());
+    ico.extend_from_slice(&png);
+    ico
+}
+
+fn render_favicon(color: &FaviconColor, format: FaviconFormat) -> Option<CachedResponse> {
+    let (content, format) = match format {
+        FaviconFormat::Ico => (render_ico(color), format),
+        FaviconFormat::Png => (render_png(color), format),
+        FaviconFormat::Svg => (render_svg(color), format),
+        FaviconFormat::Webp => match color.webp {
+            Some(webp) => (FAVICONS.get(webp)?.clone(), format),
+            None => (render_png(color), FaviconFormat::Png),
+        },
+    };
+
+    Some(CachedResponse {
+        content,
+        content_type: format.content_type().to_string(),
+    })
+}
+
+fn favicon_response(cached: CachedResponse) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(cached.content_type.as_str())
+        .insert_header(("Cache-Control", "public, max-age=60"))
+        .body(cached.content)
+}
+
+fn uncached_favicon(cached: CachedResponse) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(cached.content_type.as_str())
+        .insert_header(("Cache-Control", "no-store"))
+        .body(cached.content)
+}
+
+fn fallback_favicon(format: FaviconFormat) -> HttpResponse {
+    match render_favicon(&UNKNOWN_COLOR, format) {
+        Some(cached) => uncached_favicon(cached),
+        None => HttpResponse::NotFound().finish()
+    }
+}
+
+async fn cache_favicon(data: &AppState, cache_key: String, status: OverallStatus, format: FaviconFormat, stale: bool) -> HttpResponse {
+    let cached = match render_favicon(&status_color(status), format) {
+        Some(cached) => cached,
+        None => return HttpResponse::NotFound().finish()
+    };
+
+    if stale {
+        return uncached_favicon(cached);
+    }
+    data.badge_cache.insert(cache_key, cached.clone()).await;
+
+    favicon_response(cached)
+}
+
+pub async fn overall_favicon(path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse> {
+    let format = match FaviconFormat::from_extension(&path.into_inner()) {
+        Some(format) => format,
+        None => return Ok(HttpResponse::NotFound().finish())
+    };
+    let cache_key = format!("favicon:overall:{:?}", format);
+
+    if let Some(cached) = data.badge_cache.get(&cache_key).await {
+        return Ok(favicon_response(cached));
+    }
+
+    let loaded = match data.snapshots.load(&data.db).await {
+        Some(loaded) => loaded,
+        None => return Ok(fallback_favicon(format))
+    };
+
+    Ok(cache_favicon(&data, cache_key, overall_status(&loaded.snapshot.services, &loaded.snapshot.incidents), format, loaded.stale).await)
+}
+
+pub async fn service_favicon(path: web::Path<(String, String)>, data: web::Data<AppState>) -> Result<HttpResponse> {
+    let (service_id, extension) = path.into_inner();
+    let format = match FaviconFormat::from_extension(&extension) {
+        Some(format) => format,
+        None => return Ok(HttpResponse::NotFound().finish())
+    };
+    let cache_key = format!("favicon:{}:{:?}", service_id, format);
+
+    if let Some(cached) = data.badge_cache.get(&cache_key).await {
+        return Ok(favicon_response(cached));
+    }
+
+    let loaded = match data.snapshots.load(&data.db).await {
+        Some(loaded) => loaded,
+        None => return Ok(fallback_favicon(format))
+    };
+    let service = match loaded.snapshot.services.iter().find(|s| s.id == service_id) {
+        Some(service) => service,
+        None => return Ok(HttpResponse::NotFound().finish())
+    };
+
+    let status = service_state(service, &loaded.snapshot.incidents);
+
+    Ok(cache_favicon(&data, cache_key, status, format, loaded.stale).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(png: &[u8]) -> Vec<(&[u8], &[u8])> {
+        let mut chunks = Vec::new();
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind, data) = (&rest[4..8], &rest[8..8 + length]);
+            let crc = u32::from_be_bytes(rest[8 + length..12 + length].try_into().unwrap());
+            assert_eq!(crc, crc32(&rest[4..8 + length]));
+            chunks.push((kind, data));
+            rest = &rest[12 + length..];
+        }
+        chunks
+    }
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn png_is_well_formed() {
+        let color = status_color(OverallStatus::Degraded);
+        let png = render_png(&color);
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        let chunks = chunks(&png);
+        let kinds: Vec<&[u8]> = chunks.iter().map(|(kind, _)| *kind).collect();
+        assert_eq!(kinds, [&b"IHDR"[..], b"IDAT", b"IEND"]);
+        assert_eq!(&chunks[0].1[..8], [0, 0, 0, 32, 0, 0, 0, 32]);
+
+        let zlib = chunks[1].1;
+        let raw = &zlib[7..zlib.len() - 4];
+        assert_eq!(raw.len(), (ICON_SIZE * (ICON_SIZE * 4 + 1)) as usize);
+        assert_eq!(u32::from_be_bytes(zlib[zlib.len() - 4..].try_into().unwrap()), adler32(raw));
+
+        let pixel = |x: u32, y: u32| {
+            let offset = (y * (ICON_SIZE * 4 + 1) + 1 + x * 4) as usize;
+            &raw[offset..offset + 4]
+        };
+        assert_eq!(pixel(16, 16), [0xf1, 0xc4, 0x0f, 0xff]);
+        assert_eq!(pixel(0, 0)[3], 0);
+    }
+
+    #[test]
+    fn ico_wraps_the_png() {
+        let color = status_color(OverallStatus::Down);
+        let ico = render_ico(&color);
+        let png = render_png(&color);
+
+        assert_eq!(&ico[..6], [0, 0, 1, 0, 1, 0]);
+        assert_eq!(u32::from_le_bytes(ico[14..18].try_into().unwrap()) as usize, png.len());
+        assert_eq!(u32::from_le_bytes(ico[18..22].try_into().unwrap()), 22);
+        assert_eq!(&ico[22..], png);
+    }
+
+    #[test]
+    fn degraded_webp_falls_back_to_png() {
+        let cached = render_favicon(&status_color(OverallStatus::Degraded), FaviconFormat::Webp).unwrap();
+        assert_eq!(cached.content_type, "image/png");
+    }
+
+    #[test]
+    fn fallback_renders_the_requested_format() {
+        for format in [FaviconFormat::Ico, FaviconFormat::Png, FaviconFormat::Svg] {
+            let cached = render_favicon(&UNKNOWN_COLOR, format).unwrap();
+            assert_eq!(cached.content_type, format.content_type());
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::Utc;
+
+use crate::{
+    AppState,
+    base_url,
+    escape_xml,
+    database::IncidentFilter,
+    snapshot::unavailable,
+    status::{IncidentSummary, format_duration, summarize_incident}
+};
+
+
+const FEED_TITLE: &str = "Status Page";
+const FEED_SIZE: i64 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeedFormat {
+    Rss,
+    Atom,
+}
+
+impl FeedFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "rss",
+            FeedFormat::Atom => "atom",
+        }
+    }
+}
+
+struct Feed {
+    title: String,
+    link: String,
+    self_link: String,
+    incidents: Vec<IncidentSummary>,
+}
+
+fn incident_guid(incident: &IncidentSummary) -> String {
+    format!("urn:statuspage:incident:{}", incident.id)
+}
+
+fn incident_title(incident: &IncidentSummary) -> String {
+    let state = if incident.end_time.is_some() { "Resolved" } else { "Ongoing" };
+    format!("[{}] {}: {}", state, incident.service_name, incident.description)
+}
+
+fn incident_summary(incident: &IncidentSummary) -> String {
+    let started = incident.start_time.format("%Y-%m-%d %H:%M:%S UTC");
+
+    match (incident.end_time, incident.duration_seconds) {
+        (Some(end_time), Some(duration)) => format!(
+            "{} Started: {}. Resolved: {} after {}.",
+            incident.description,
+            started,
+            end_time.format("%Y-%m-%d %H:%M:%S UTC"),
+            format_duration(duration)
+        ),
+        _ => format!("{} Started: {}. Ongoing.", incident.description, started)
+    }
+}
+
+fn render_rss(feed: &Feed) -> String {
+    let last_build = feed.incidents.iter()
+        .map(|i| i.end_time.unwrap_or(i.start_time))
+        .max()
+        .unwrap_or_else(Utc::now);
+
+    let items: String = feed.incidents.iter().map(|incident| {
+        format!(
+            "<item><title>{}</title><link>{}/service/{}</link><guid isPermaLink=\"false\">{}</guid>\
+            <pubDate>{}</pubDate><description>{}</description></item>",
+            escape_xml(&incident_title(incident)),
+            escape_xml(&feed.link),
+            escape_xml(&incident.service_id),
+            incident_guid(incident),
+            incident.start_time.to_rfc2822(),
+            escape_xml(&incident_summary(incident))
+        )
+    }).collect();
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+        <rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\"><channel>\
+        <title>{}</title><link>{}</link><description>Incidents reported on {}</description>\
+        <atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>\
+        <lastBuildDate>{}</lastBuildDate>{}</channel></rss>",
+        escape_xml(&feed.title),
+        escape_xml(&feed.link),
+        escape_xml(&feed.title),
+        escape_xml(&feed.self_link),
+        last_build.to_rfc2822(),
+        items
+    )
+}
+
+fn render_atom(feed: &Feed) -> String {
+    let updated = feed.incidents.iter()
+        .map(|i| i.end_time.unwrap_or(i.start_time))
+        .max()
+        .unwrap_or_else(Utc::now);
+
+    let entries: String = feed.incidents.iter().map(|incident| {
+        format!(
+            "<entry><id>{}</id><title>{}</title><link href=\"{}/service/{}\"/>\
+            <published>{}</published><updated>{}</updated><summary>{}</summary></entry>",
+            incident_guid(incident),
+            escape_xml(&incident_title(incident)),
+            escape_xml(&feed.link),
+            escape_xml(&incident.service_id),
+            incident.start_time.to_rfc3339(),
+            incident.end_time.unwrap_or(incident.start_time).to_rfc3339(),
+            escape_xml(&incident_summary(incident))
+        )
+    }).collect();
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+        <feed xmlns=\"http://www.w3.org/2005/Atom\">\
+        <id>{}</id><title>{}</title><link href=\"{}\"/><link href=\"{}\" rel=\"self\"/>\
+        <updated>{}</updated><author><name>{}</name></author>{}</feed>",
+        escape_xml(&feed.self_link),
+        escape_xml(&feed.title),
+        escape_xml(&feed.link),
+        escape_xml(&feed.self_link),
+        updated.to_rfc3339(),
+        FEED_TITLE,
+        entries
+    )
+}
+
+async fn feed_response(req: &HttpRequest, data: &AppState, format: FeedFormat, service_id: Option<String>) -> Result<HttpResponse> {
+    let url = base_url(req);
+    let cache_key = format!("feed:{}:{}:{}", format.extension(), service_id.as_deref().unwrap_or_default(), url);
+
+    if let Some(cached_response) = data.api_cache.get(&cache_key).await {
+        return Ok(HttpResponse::Ok()
+            .content_type(format.content_type())
+            .insert_header(("Cache-Control", "public, max-age=60"))
+            .body(cached_response));
+    }
+
+    let (title, link, self_link) = match &service_id {
+        Some(service_id) => {
+            let service_names = data.db.list_service_names().await
+                .map_err(|e| {
+                    eprintln!("Database error: {}", e);
+                    unavailable()
+                })?;
+            let name = match service_names.into_iter().find(|(id, _)| id == service_id) {
+                Some((_, name)) => name,
+                None => return Ok(HttpResponse::NotFound().finish())
+            };
+            (
+                format!("{} - {}", name, FEED_TITLE),
+                url.clone(),
+                format!("{}/service/{}/feed.{}", url, service_id, format.extension())
+            )
+        },
+        None => (FEED_TITLE.to_string(), url.clone(), format!("{}/feed.{}", url, format.extension()))
+    };
+
+    let filter = IncidentFilter {
+        service_id,
+        limit: FEED_SIZE,
+        ..Default::default()
+    };
+    let (incidents, _) = data.db.search_incidents(&filter).await
+        .map_err(|e| {
+            eprintln!("Database error: {}", e);
+            unavailable()
+        })?;
+
+    let feed = Feed {
+        title,
+        link,
+        self_link,
+        incidents: incidents.into_iter().map(summarize_incident).collect(),
+    };
+
+    let body = match format {
+        FeedFormat::Rss => render_rss(&feed),
+        FeedFormat::Atom => render_atom(&feed),
+    };
+    data.api_cache.insert(cache_key, body.clone()).await;
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(("Cache-Control", "public, max-age=60"))
+        .body(body))
+}
+
+pub async fn rss_feed(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
+    feed_response(&req, &data, FeedFormat::Rss, None).await
+}
+
+pub async fn atom_feed(req: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
+    feed_response(&req, &data, FeedFormat::Atom, None).await
+}
+
+pub async fn service_rss_feed(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse> {
+    feed_response(&req, &data, FeedFormat::Rss, Some(path.into_inner())).await
+}
+
+pub async fn service_atom_feed(req: HttpRequest, path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse> {
+    feed_response(&req, &data, FeedFormat::Atom, Some(path.into_inner())).await
+}
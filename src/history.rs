@@ -0,0 +1,140 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    database::{DbPool, IncidentFilter, MonitoringError},
+    status::{IncidentSummary, summarize_incident}
+};
+
+
+const DEFAULT_PER_PAGE: u32 = 20;
+const MAX_PER_PAGE: u32 = 100;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryQuery {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+    pub service: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IncidentPage {
+    pub incidents: Vec<IncidentSummary>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: i64,
+    pub total_pages: u32,
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn parse_bound(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD or RFC 3339", value))?;
+    let time = if end_of_day {
+        NaiveTime::from_hms_milli_opt(23, 59, 59, 999)
+    } else {
+        NaiveTime::from_hms_opt(0, 0, 0)
+    }.unwrap_or_default();
+
+    Ok(date.and_time(time).and_utc())
+}
+
+impl HistoryQuery {
+    pub fn page(&self) -> u32 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn per_page(&self) -> u32 {
+        self.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE)
+    }
+
+    pub fn service(&self) -> Option<&str> {
+        non_empty(&self.service)
+    }
+
+    pub fn from(&self) -> Option<&str> {
+        non_empty(&self.from)
+    }
+
+    pub fn to(&self) -> Option<&str> {
+        non_empty(&self.to)
+    }
+
+    pub fn to_filter(&self) -> Result<IncidentFilter, String> {
+        let from = self.from().map(|v| parse_bound(v, false)).transpose()?;
+        let to = self.to().map(|v| parse_bound(v, true)).transpose()?;
+
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err("'from' must not be after 'to'".to_string());
+            }
+        }
+
+        Ok(IncidentFilter {
+            service_id: self.service().map(str::to_string),
+            from,
+            to,
+            limit: self.per_page() as i64,
+            offset: (self.page() as i64 - 1) * self.per_page() as i64,
+        })
+    }
+
+    pub fn cache_key(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}",
+            self.page(),
+            self.per_page(),
+            self.service().unwrap_or_default(),
+            self.from().unwrap_or_default(),
+            self.to().unwrap_or_default()
+        )
+    }
+}
+
+pub async fn load_history(db: &DbPool, query: &HistoryQuery, filter: &IncidentFilter) -> Result<IncidentPage, MonitoringError> {
+    let (incidents, total) = db.search_incidents(filter).await?;
+    let per_page = query.per_page();
+
+    Ok(IncidentPage {
+        incidents: incidents.into_iter().map(summarize_incident).collect(),
+        page: query.page(),
+        per_page,
+        total,
+        total_pages: (total as u32).div_ceil(per_page).max(1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bound_accepts_rfc3339() {
+        let bound = parse_bound("2024-05-01T12:30:00+02:00", false).unwrap();
+        assert_eq!(bound.to_rfc3339(), "2024-05-01T10:30:00+00:00");
+    }
+
+    #[test]
+    fn parse_bound_expands_dates_to_day_boundaries() {
+        assert_eq!(parse_bound("2024-05-01", false).unwrap().to_rfc3339(), "2024-05-01T00:00:00+00:00");
+        assert_eq!(parse_bound("2024-05-01", true).unwrap().to_rfc3339(), "2024-05-01T23:59:59.999+00:00");
+    }
+
+    #[test]
+    fn parse_bound_rejects_other_formats() {
+        assert_eq!(
+            parse_bound("01/05/2024", false).unwrap_err(),
+            "Invalid date '01/05/2024', expected YYYY-MM-DD or RFC 3339"
+        );
+        assert!(parse_bound("2024-02-30", false).is_err());
+    }
+}
@@ -1,15 +1,48 @@
 mod database;
 
-use database::{DbPool, init_database};
+use database::{DbPool, IncidentImpact, IncidentStatus, IncidentUpdate, MonitoringError};
 
 mod config;
-use config::{Config, get_config};
+use config::{Command, Config, get_config};
+
+mod migrations;
+
+mod status;
+use status::{ServiceSummary, StatusOverview, build_overview, build_service_detail, format_duration, summarize_incident};
+
+mod history;
+use history::{HistoryQuery, load_history};
+
+mod api;
+mod atlassian;
+mod badge;
+mod feed;
+mod favicon;
+
+mod metrics;
+use metrics::MeteredCache;
+
+mod events;
+use events::EventHub;
+
+mod snapshot;
+use snapshot::{SnapshotStore, unavailable};
+
+mod rollups;
+
+mod retention;
+use retention::RetentionPolicy;
+
+mod monitor;
+mod assertions;
+
+mod outages;
 
 use std::{
     error::Error,
     time::Duration,
     sync::Arc,
-    collections::{VecDeque, HashMap},
+    collections::HashMap,
     fs,
     path::Path
 };
@@ -18,8 +51,8 @@ use moka::future::Cache;
 use tera::Tera;
 use lazy_static::lazy_static;
 use dotenv::dotenv;
-use chrono::Utc;
-use actix_web::{main, web, App, HttpResponse, HttpServer, Result, Responder};
+use chrono::{DateTime, Utc};
+use actix_web::{main, web, App, HttpRequest, HttpResponse, HttpServer, Result, Responder};
 
 
 lazy_static! {
@@ -35,11 +68,6 @@ lazy_static! {
         tera
     };
 
-    static ref FAVICON_ICO: Option<Vec<u8>> = {
-        let favicon_path = Path::new("favicons/favicon.ico");
-        fs::read(favicon_path).ok()
-    };
-
     static ref FAVICONS: HashMap<String, Vec<u8>> = {
         let mut icons = HashMap::new();
         let favicon_dir = Path::new("favicons");
@@ -69,19 +97,42 @@ lazy_static! {
     static ref ROBOTS_TXT: &'static str = "User-agent: *\nAllow: /\n";
 }
 
-pub fn calculate_uptime(data: &VecDeque<u32>, window: usize) -> f64 {
-    if data.is_empty() {
-        return 100.0;
-    }
-    
-    let actual_window = std::cmp::min(window, data.len());
-    
-    let up_count = data.iter()
-        .take(actual_window)
-        .filter(|&&x| x > 0)
-        .count();
-    
-    (up_count as f64 / actual_window as f64) * 100.0
+fn base_url(req: &HttpRequest) -> String {
+    let info = req.connection_info();
+    format!("{}://{}", info.scheme(), info.host())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+async fn load_overview(data: &AppState) -> Result<StatusOverview> {
+    let loaded = data.snapshots.load(&data.db).await.ok_or_else(unavailable)?;
+
+    let mut overview = build_overview(
+        &loaded.snapshot.services,
+        &loaded.snapshot.groups,
+        loaded.snapshot.incidents.clone(),
+        &loaded.snapshot.maintenances
+    );
+    overview.stale_since = loaded.stale_since();
+
+    Ok(overview)
+}
+
+fn formatted_updates(updates: &[IncidentUpdate]) -> Vec<serde_json::Value> {
+    updates.iter().rev().map(|update| {
+        serde_json::json!({
+            "status": update.status,
+            "status_label": update.status.label(),
+            "created_at": update.created_at.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            "body": update.body
+        })
+    }).collect()
 }
 
 async fn index_service(data: web::Data<AppState>) -> Result<HttpResponse> {
@@ -92,116 +143,74 @@ async fn index_service(data: web::Data<AppState>) -> Result<HttpResponse> {
             .body(cached_response));
     }
 
-    let (services, incidents) = tokio::join!(
-        data.db.list_services(),
-        data.db.list_incidents(false)
-    );
-    let services = services.unwrap_or_default();
-    let incidents = incidents.unwrap_or_default();
+    let overview = load_overview(&data).await?;
+    let stale = overview.stale_since.is_some();
 
-    let overall_status = match services.iter().filter(|s| !s.is_online).count() {
-        0 => "up",
-        n if n == services.len() => "down",
-        _ => "partial"
+    let process_service = |service: &ServiceSummary| {
+        serde_json::json!({
+            "id": service.id,
+            "name": service.name,
+            "description": service.description,
+            "public_url": service.public_url,
+            "tags": service.tags,
+            "uptime": format!("{:.2}", service.uptime.last_24h),
+            "status": service.status
+        })
     };
 
-    let service_count = services.len() as f64;
-    let mut processed_services = Vec::with_capacity(services.len());
-    let mut total_uptimes = [0.0; 4];
-
-    for service in &services {
-        let response_times: VecDeque<u32> = service.response_times
-            .iter()
-            .map(|&x| if x > 0 { x as u32 } else { 0 })
-            .collect();
-
-        total_uptimes[0] += calculate_uptime(&response_times, 1440);
-        total_uptimes[1] += calculate_uptime(&response_times, 10080);
-        total_uptimes[2] += calculate_uptime(&response_times, 43200);
-        total_uptimes[3] += calculate_uptime(&response_times, 129600);
-
-        let uptime_24h = calculate_uptime(&response_times, 1440);
-        let total_points = response_times.len();
-        let points_per_day = 1440;
-        let available_days = (total_points as f64 / points_per_day as f64).ceil() as usize;
-        let max_days = 90;
-
-        let mut status_segments = Vec::with_capacity(max_days);
-
-        if available_days < max_days {
-            status_segments.extend(std::iter::repeat(serde_json::json!({
-                "name": "no-data",
-                "description": "No data"
-            })).take(max_days - available_days));
-        }
-
-        for day in 0..available_days.min(max_days) {
-            let start_idx = day * points_per_day;
-            if start_idx >= total_points {
-                break;
-            }
-
-            let end_idx = std::cmp::min((day + 1) * points_per_day, total_points);
-            let day_data: Vec<_> = response_times.iter()
-                .skip(start_idx)
-                .take(end_idx - start_idx)
-                .cloned()
-                .collect();
-
-            if !day_data.is_empty() {
-                let down_count = day_data.iter().filter(|&&x| x == 0).count();
-                let down_percentage = (down_count as f64 / day_data.len() as f64) * 100.0;
-
-                let (status, description) = if down_percentage > 50.0 {
-                    ("down", format!("{:.1}%", 100.0 - down_percentage))
-                } else if down_percentage > 25.0 {
-                    ("partial", format!("{:.1}%", 100.0 - down_percentage))
-                } else {
-                    ("up", format!("{:.1}%", 100.0 - down_percentage))
-                };
-
-                status_segments.push(serde_json::json!({
-                    "name": status,
-                    "description": description
-                }));
-            } else {
-                status_segments.push(serde_json::json!({
-                    "name": "no-data",
-                    "description": "No data"
-                }));
-            }
-        }
+    let ungrouped: Vec<_> = overview.services.iter()
+        .filter(|service| !overview.groups.iter().any(|g| service.group_id.as_ref() == Some(&g.id)))
+        .map(process_service)
+        .collect();
 
-        processed_services.push(serde_json::json!({
-            "id": service.id,
-            "name": service.name,
-            "uptime": format!("{:.2}", uptime_24h),
-            "status": status_segments
-        }));
+    let mut sections = Vec::with_capacity(overview.groups.len() + 1);
+    if !ungrouped.is_empty() {
+        sections.push(serde_json::json!({ "services": ungrouped }));
     }
+    sections.extend(overview.groups.iter().map(|group| {
+        serde_json::json!({
+            "id": group.id,
+            "name": group.name,
+            "status": group.status,
+            "uptime": format!("{:.2}", group.uptime.last_24h),
+            "services": overview.services.iter()
+                .filter(|service| service.group_id.as_ref() == Some(&group.id))
+                .map(process_service)
+                .collect::<Vec<_>>()
+        })
+    }));
 
-    let [avg_uptime_24h, avg_uptime_7d, avg_uptime_30d, avg_uptime_90d] = if service_count > 0.0 {
-        total_uptimes.map(|total| total / service_count)
-    } else {
-        [100.0; 4]
-    };
-
-    let formatted_incidents: Vec<_> = incidents.into_iter().map(|incident| {
+    let formatted_incidents: Vec<_> = overview.incidents.iter().map(|incident| {
         serde_json::json!({
+            "id": incident.id,
             "service_name": incident.service_name,
             "start_time": incident.start_time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
-            "description": incident.description
+            "description": incident.description,
+            "updates": formatted_updates(&incident.updates)
+        })
+    }).collect();
+
+    let formatted_maintenances: Vec<_> = overview.maintenances.iter().map(|maintenance| {
+        serde_json::json!({
+            "id": maintenance.id,
+            "services": maintenance.service_names.join(", "),
+            "start_time": maintenance.start_time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            "end_time": maintenance.end_time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            "in_progress": maintenance.in_progress,
+            "description": maintenance.description
         })
     }).collect();
 
     let mut ctx = tera::Context::new();
-    ctx.insert("status", &overall_status);
-    ctx.insert("services", &processed_services);
+    ctx.insert("status", &overview.status);
+    ctx.insert("sections", &sections);
+    ctx.insert("maintenances", &formatted_maintenances);
     ctx.insert("situations", &formatted_incidents);
-    ctx.insert("uptime_24h", &format!("{:.2}", avg_uptime_24h));
-    ctx.insert("uptime_7d", &format!("{:.2}", avg_uptime_7d));
-    ctx.insert("uptime_30d", &format!("{:.2}", avg_uptime_30d));
-    ctx.insert("uptime_90d", &format!("{:.2}", avg_uptime_90d));
+    ctx.insert("uptime_24h", &format!("{:.2}", overview.uptime.last_24h));
+    ctx.insert("uptime_7d", &format!("{:.2}", overview.uptime.last_7d));
+    ctx.insert("uptime_30d", &format!("{:.2}", overview.uptime.last_30d));
+    ctx.insert("uptime_90d", &format!("{:.2}", overview.uptime.last_90d));
+    ctx.insert("stale_since", &overview.stale_since.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string()));
     ctx.insert("load_time", &Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string());
 
     let rendered = TEMPLATES.render("index.html", &ctx)
@@ -213,11 +222,13 @@ async fn index_service(data: web::Data<AppState>) -> Result<HttpResponse> {
             actix_web::error::ErrorInternalServerError("Template error")
         })?;
 
-    data.index_cache.insert("index".to_string(), rendered.clone()).await;
+    if !stale {
+        data.index_cache.insert("index".to_string(), rendered.clone()).await;
+    }
 
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
-        .insert_header(("Cache-Control", "public, max-age=30"))
+        .insert_header(("Cache-Control", if stale { "no-store" } else { "public, max-age=30" }))
         .body(rendered))
 }
 
@@ -231,101 +242,18 @@ async fn service_detail_service(path: web::Path<String>, data: web::Data<AppStat
             .body(cached_response));
     }
 
-    let services = data.db.list_services().await.unwrap_or_default();
-    let service = match services.iter().find(|s| s.id == service_id) {
+    let loaded = data.snapshots.load(&data.db).await.ok_or_else(unavailable)?;
+    let service = match loaded.snapshot.services.iter().find(|s| s.id == service_id) {
         Some(service) => service,
         None => return Ok(HttpResponse::NotFound().finish())
     };
+    let stale_since = loaded.stale_since();
 
-    let response_times: VecDeque<u32> = service.response_times
-        .iter()
-        .map(|&x| if x > 0 { x as u32 } else { 0 })
-        .collect();
-
-    let uptimes = [1440, 10080, 43200, 129600]
-        .map(|window| calculate_uptime(&response_times, window));
-    
-    let total_points = response_times.len();
-    let points_per_day = 1440;
-    let available_days = (total_points as f64 / points_per_day as f64).ceil() as usize;
-    let max_days = 90;
-    
-    let mut status_segments = Vec::with_capacity(max_days);
-    
-    if available_days < max_days {
-        status_segments.extend(std::iter::repeat(serde_json::json!({
-            "name": "no-data",
-            "description": "No data"
-        })).take(max_days - available_days));
-    }
-
-    for day in 0..available_days.min(max_days) {
-        let start_idx = day * points_per_day;
-        if start_idx >= total_points {
-            break;
-        }
+    let detail = build_service_detail(service, &loaded.snapshot.incidents);
 
-        let end_idx = std::cmp::min((day + 1) * points_per_day, total_points);
-        let day_data: Vec<_> = response_times.iter()
-            .skip(start_idx)
-            .take(end_idx - start_idx)
-            .cloned()
-            .collect();
-
-        if !day_data.is_empty() {
-            let down_count = day_data.iter().filter(|&&x| x == 0).count();
-            let down_percentage = (down_count as f64 / day_data.len() as f64) * 100.0;
-
-            let (status, description) = if down_percentage > 50.0 {
-                ("down", format!("{:.1}%", 100.0 - down_percentage))
-            } else if down_percentage > 25.0 {
-                ("partial", format!("{:.1}%", 100.0 - down_percentage))
-            } else {
-                ("up", format!("{:.1}%", 100.0 - down_percentage))
-            };
-
-            status_segments.push(serde_json::json!({
-                "name": status,
-                "description": description
-            }));
-        } else {
-            status_segments.push(serde_json::json!({
-                "name": "no-data",
-                "description": "No data"
-            }));
-        }
-    }
-
-    let last_30_days: Vec<u32> = response_times.iter()
-        .take(43200)
-        .copied()
-        .collect();
-
-    let days = (last_30_days.len() + points_per_day - 1) / points_per_day;
-    let daily_averages: Vec<u32> = (0..days)
-        .filter_map(|day| {
-            let start = day * points_per_day;
-            let end = std::cmp::min((day + 1) * points_per_day, last_30_days.len());
-            let day_data = &last_30_days[start..end];
-            
-            if !day_data.is_empty() {
-                let (sum, count) = day_data.iter()
-                    .filter(|&&x| x > 0)
-                    .fold((0u64, 0usize), |(sum, count), &x| (sum + x as u64, count + 1));
-                if count > 0 {
-                    Some((sum / count as u64) as u32)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    let (max_daily_avg, min_daily_avg) = if !daily_averages.is_empty() {
-        let max = daily_averages.iter().copied().max().unwrap_or(0) + 50;
-        let min = daily_averages.iter()
+    let (max_daily_avg, min_daily_avg) = if !detail.daily_averages.is_empty() {
+        let max = detail.daily_averages.iter().copied().max().unwrap_or(0) + 50;
+        let min = detail.daily_averages.iter()
             .copied()
             .filter(|&x| x > 0)
             .min()
@@ -336,43 +264,8 @@ async fn service_detail_service(path: web::Path<String>, data: web::Data<AppStat
         (0, 0)
     };
 
-    let (max_response_time, min_response_time, avg_response_time) = {
-        let max = last_30_days.iter().copied().max().unwrap_or(0);
-        let min = last_30_days.iter().filter(|&&x| x > 0).min().copied().unwrap_or(0);
-        let avg = if !daily_averages.is_empty() {
-            let sum: u64 = daily_averages.iter().map(|&x| x as u64).sum();
-            (sum / daily_averages.len() as u64) as u32
-        } else {
-            0
-        };
-        (max, min, avg)
-    };
-
     let graph_points = {
-        let valid_points: Vec<u32> = last_30_days.iter()
-            .filter(|&&x| x > 0)
-            .copied()
-            .collect();
-
-        let points = if valid_points.len() <= 30 {
-            valid_points
-        } else {
-            let step = valid_points.len() / 30;
-            (0..30)
-                .filter_map(|i| {
-                    let start = i * step;
-                    let end = start + step;
-                    let slice = &valid_points[start..std::cmp::min(end, valid_points.len())];
-                    if !slice.is_empty() {
-                        let sum: u64 = slice.iter().map(|&x| x as u64).sum();
-                        Some((sum / slice.len() as u64) as u32)
-                    } else {
-                        None
-                    }
-                })
-                .collect()
-        };
-
+        let points = &detail.graph_points;
         let range = max_daily_avg.saturating_sub(min_daily_avg);
         points.iter().enumerate()
             .map(|(i, &value)| {
@@ -398,16 +291,40 @@ async fn service_detail_service(path: web::Path<String>, data: web::Data<AppStat
 
     let mut ctx = tera::Context::new();
     ctx.insert("service", &service);
-    ctx.insert("status", &status_segments);
-    ctx.insert("uptime_24h", &format!("{:.2}", uptimes[0]));
-    ctx.insert("uptime_7d", &format!("{:.2}", uptimes[1]));
-    ctx.insert("uptime_30d", &format!("{:.2}", uptimes[2]));
-    ctx.insert("uptime_90d", &format!("{:.2}", uptimes[3]));
+    ctx.insert("state", &detail.state);
+    ctx.insert("status", &detail.status);
+    ctx.insert("uptime_24h", &format!("{:.2}", detail.uptime.last_24h));
+    ctx.insert("uptime_7d", &format!("{:.2}", detail.uptime.last_7d));
+    ctx.insert("uptime_30d", &format!("{:.2}", detail.uptime.last_30d));
+    ctx.insert("uptime_90d", &format!("{:.2}", detail.uptime.last_90d));
+    ctx.insert("regions", &detail.regions.iter().map(|region| {
+        serde_json::json!({
+            "region": region.region,
+            "state": match (region.fresh, region.is_up) {
+                (true, Some(true)) => "up",
+                (true, Some(false)) => "down",
+                _ => "unknown"
+            },
+            "last_checked": region.last_checked.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string()),
+            "uptime_24h": format!("{:.2}", region.uptime.last_24h),
+            "uptime_90d": format!("{:.2}", region.uptime.last_90d)
+        })
+    }).collect::<Vec<_>>());
+    ctx.insert("certificate", &detail.certificate.as_ref().map(|certificate| {
+        serde_json::json!({
+            "expires_at": certificate.expires_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+            "days_left": certificate.days_left.max(0),
+            "expired": certificate.expires_at <= Utc::now(),
+            "expiring": certificate.expiring
+        })
+    }));
+    ctx.insert("last_error", &detail.last_error);
     ctx.insert("graph_points", &graph_points);
     ctx.insert("y_axis_steps", &y_axis_steps);
-    ctx.insert("avg_response_time", &avg_response_time);
-    ctx.insert("max_response_time", &max_response_time);
-    ctx.insert("min_response_time", &min_response_time);
+    ctx.insert("avg_response_time", &detail.response_time.avg);
+    ctx.insert("max_response_time", &detail.response_time.max);
+    ctx.insert("min_response_time", &detail.response_time.min);
+    ctx.insert("stale_since", &stale_since.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string()));
     ctx.insert("load_time", &Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string());
 
     let rendered = TEMPLATES.render("service.html", &ctx)
@@ -419,42 +336,143 @@ async fn service_detail_service(path: web::Path<String>, data: web::Data<AppStat
             actix_web::error::ErrorInternalServerError("Template error")
         })?;
 
-    data.service_cache.insert(service_id, rendered.clone()).await;
+    if stale_since.is_none() {
+        data.service_cache.insert(service_id, rendered.clone()).await;
+    }
 
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
-        .insert_header(("Cache-Control", "public, max-age=30"))
+        .insert_header(("Cache-Control", if stale_since.is_some() { "no-store" } else { "public, max-age=30" }))
         .body(rendered))
 }
 
-#[derive(Clone)]
-struct CachedResponse {
-    content: Vec<u8>,
-    content_type: String,
-}
+async fn history_service(query: web::Query<HistoryQuery>, data: web::Data<AppState>) -> Result<HttpResponse> {
+    let filter = query.to_filter().map_err(actix_web::error::ErrorBadRequest)?;
+    let cache_key = format!("history:{}", query.cache_key());
 
-async fn favicon_service(data: web::Data<AppState>) -> Result<HttpResponse> {
-    if let Some(cached) = data.static_cache.get("favicon.ico").await {
+    if let Some(cached_response) = data.index_cache.get(&cache_key).await {
         return Ok(HttpResponse::Ok()
-            .content_type(cached.content_type.as_str())
-            .insert_header(("Cache-Control", "public, max-age=86400"))
-            .body(cached.content));
+            .content_type("text/html; charset=utf-8")
+            .insert_header(("Cache-Control", "public, max-age=30"))
+            .body(cached_response));
     }
 
-    if let Some(content) = FAVICON_ICO.as_ref() {
-        let cached = CachedResponse {
-            content: content.clone(),
-            content_type: "image/x-icon".to_string(),
-        };
-        data.static_cache.insert("favicon.ico".to_string(), cached.clone()).await;
-        
-        Ok(HttpResponse::Ok()
-            .content_type("image/x-icon")
-            .insert_header(("Cache-Control", "public, max-age=86400"))
-            .body(content.clone()))
-    } else {
-        Ok(HttpResponse::NotFound().finish())
+    let (page, service_names) = tokio::join!(
+        load_history(&data.db, &query, &filter),
+        data.db.list_service_names()
+    );
+    let page = page.map_err(|e| {
+        eprintln!("Database error: {}", e);
+        unavailable()
+    })?;
+    let service_names = service_names.unwrap_or_else(|e| {
+        eprintln!("Database error: {}", e);
+        Vec::new()
+    });
+
+    let formatted_incidents: Vec<_> = page.incidents.iter().map(|incident| {
+        serde_json::json!({
+            "id": incident.id,
+            "service_id": incident.service_id,
+            "service_name": incident.service_name,
+            "start_time": incident.start_time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            "end_time": incident.end_time.map(|end| end.format("%Y-%m-%d %H:%M:%S UTC").to_string()),
+            "duration": incident.duration_seconds.map(format_duration),
+            "description": incident.description
+        })
+    }).collect();
+
+    let services: Vec<_> = service_names.iter().map(|(id, name)| {
+        serde_json::json!({
+            "id": id,
+            "name": name
+        })
+    }).collect();
+
+    let mut ctx = tera::Context::new();
+    ctx.insert("incidents", &formatted_incidents);
+    ctx.insert("services", &services);
+    ctx.insert("selected_service", &query.service().unwrap_or_default());
+    ctx.insert("from", &query.from().unwrap_or_default());
+    ctx.insert("to", &query.to().unwrap_or_default());
+    ctx.insert("page", &page.page);
+    ctx.insert("total_pages", &page.total_pages);
+    ctx.insert("total", &page.total);
+    ctx.insert("load_time", &Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string());
+
+    let rendered = TEMPLATES.render("history.html", &ctx)
+        .map_err(|e| {
+            eprintln!("Template error: {}", e);
+            if let Some(source) = e.source() {
+                eprintln!("Caused by: {}", source);
+            }
+            actix_web::error::ErrorInternalServerError("Template error")
+        })?;
+
+    data.index_cache.insert(cache_key, rendered.clone()).await;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header(("Cache-Control", "public, max-age=30"))
+        .body(rendered))
+}
+
+async fn incident_service(path: web::Path<i32>, data: web::Data<AppState>) -> Result<HttpResponse> {
+    let incident_id = path.into_inner();
+    let cache_key = format!("incident:{}", incident_id);
+
+    if let Some(cached_response) = data.index_cache.get(&cache_key).await {
+        return Ok(HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .insert_header(("Cache-Control", "public, max-age=30"))
+            .body(cached_response));
     }
+
+    let incident = data.db.get_incident(incident_id).await.map_err(|e| {
+        eprintln!("Database error: {}", e);
+        unavailable()
+    })?;
+    let incident = match incident {
+        Some(incident) => summarize_incident(incident),
+        None => return Ok(HttpResponse::NotFound().finish())
+    };
+
+    let mut ctx = tera::Context::new();
+    ctx.insert("incident", &serde_json::json!({
+        "id": incident.id,
+        "service_id": incident.service_id,
+        "service_name": incident.service_name,
+        "start_time": incident.start_time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        "end_time": incident.end_time.map(|end| end.format("%Y-%m-%d %H:%M:%S UTC").to_string()),
+        "duration": incident.duration_seconds.map(format_duration),
+        "description": incident.description,
+        "status": incident.status,
+        "status_label": incident.status.label()
+    }));
+    ctx.insert("updates", &formatted_updates(&incident.updates));
+    ctx.insert("load_time", &Utc::now().format("%Y-%m-%d %H:%M:%S UTC").to_string());
+
+    let rendered = TEMPLATES.render("incident.html", &ctx)
+        .map_err(|e| {
+            eprintln!("Template error: {}", e);
+            if let Some(source) = e.source() {
+                eprintln!("Caused by: {}", source);
+            }
+            actix_web::error::ErrorInternalServerError("Template error")
+        })?;
+
+    data.index_cache.insert(cache_key, rendered.clone()).await;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header(("Cache-Control", "public, max-age=30"))
+        .body(rendered))
+}
+
+#[derive(Clone)]
+struct CachedResponse {
+    content: Vec<u8>,
+    content_type: String,
 }
 
 async fn favicons_service(path: web::Path<String>, data: web::Data<AppState>) -> Result<HttpResponse> {
@@ -512,9 +530,13 @@ async fn ping_service() -> impl Responder {
 #[derive(Clone)]
 struct AppState {
     db: Arc<DbPool>,
-    index_cache: Arc<Cache<String, String>>,
-    service_cache: Arc<Cache<String, String>>,
-    static_cache: Arc<Cache<String, CachedResponse>>,
+    index_cache: Arc<MeteredCache<String>>,
+    service_cache: Arc<MeteredCache<String>>,
+    api_cache: Arc<MeteredCache<String>>,
+    badge_cache: Arc<MeteredCache<CachedResponse>>,
+    static_cache: Arc<MeteredCache<CachedResponse>>,
+    events: Arc<EventHub>,
+    snapshots: Arc<SnapshotStore>,
 }
 
 #[main]
@@ -530,9 +552,30 @@ async fn main() -> std::io::Result<()> {
         config.database_user.clone(),
         config.database_password.clone()
     ).await.expect("Failed to create database pool");
-    init_database(&db_pool).await.expect("Failed to initialize database");
+
+    match &config.command {
+        Some(Command::Migrate { status }) => return run_migrate_command(&db_pool, *status).await,
+        Some(Command::UpdateIncident { incident, status, message, impact }) => {
+            return run_update_incident_command(&db_pool, *incident, status, message, impact.as_deref()).await;
+        },
+        Some(Command::ScheduleMaintenance { start, end, description, services }) => {
+            return run_schedule_maintenance_command(&db_pool, &config.retention, *start, *end, description, services).await;
+        },
+        Some(Command::Prune { dry_run }) => return run_prune_command(&db_pool, &config.retention, *dry_run).await,
+        None => {}
+    }
+
+    let applied = migrations::migrate(&db_pool).await.expect("Failed to migrate database");
+    for migration in applied {
+        println!("Applied migration {} ({})", migration.version, migration.name);
+    }
+    if let Err(e) = db_pool.refresh_rollups(None, true).await {
+        eprintln!("Rollup refresh error: {}", e);
+    }
+    metrics::init();
 
     let index_cache = Cache::builder()
+        .max_capacity(10_000)
         .time_to_live(Duration::from_secs(30))
         .build();
         
@@ -540,20 +583,148 @@ async fn main() -> std::io::Result<()> {
         .time_to_live(Duration::from_secs(30))
         .build();
 
+    let api_cache = Cache::builder()
+        .max_capacity(10_000)
+        .time_to_live(Duration::from_secs(30))
+        .build();
+
+    let badge_cache = Cache::builder()
+        .max_capacity(10_000)
+        .time_to_live(Duration::from_secs(60))
+        .build();
+
     let static_cache = Cache::builder()
         .time_to_live(Duration::from_secs(3600))
         .build();
     
+    let db_pool = Arc::new(db_pool);
+    let event_hub = Arc::new(EventHub::new());
+    let snapshots = Arc::new(SnapshotStore::default());
+    events::spawn_watcher(db_pool.clone(), event_hub.clone(), snapshots.clone());
+    rollups::spawn_refresher(db_pool.clone());
+    retention::spawn_pruner(db_pool.clone(), config.retention);
+    if let Some(monitor) = config.monitor.clone() {
+        println!("Monitoring services from region {} every {}s", monitor.region, monitor.interval.as_secs());
+        monitor::spawn_monitor(db_pool.clone(), monitor).expect("Failed to start monitor");
+    }
+    if let Some(policy) = config.outages {
+        outages::spawn_detector(db_pool.clone(), policy);
+    }
+
     let app_state = AppState {
-        db: Arc::new(db_pool),
-        index_cache: Arc::new(index_cache),
-        service_cache: Arc::new(service_cache),
-        static_cache: Arc::new(static_cache),
+        db: db_pool,
+        index_cache: Arc::new(MeteredCache::new("index", index_cache)),
+        service_cache: Arc::new(MeteredCache::new("service", service_cache)),
+        api_cache: Arc::new(MeteredCache::new("api", api_cache)),
+        badge_cache: Arc::new(MeteredCache::new("badge", badge_cache)),
+        static_cache: Arc::new(MeteredCache::new("static", static_cache)),
+        events: event_hub,
+        snapshots,
     };
 
     run_web_server(app_state, &config).await
 }
 
+async fn run_migrate_command(db_pool: &DbPool, status: bool) -> std::io::Result<()> {
+    if status {
+        let migrations = migrations::migration_status(db_pool).await
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+        println!("{:<9}{:<40}Applied at", "Version", "Name");
+        for migration in migrations {
+            let applied_at = match (migration.applied_at, migration.known) {
+                (Some(applied_at), true) => applied_at.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                (Some(applied_at), false) => format!("{} (unknown to this build)", applied_at.format("%Y-%m-%d %H:%M:%S UTC")),
+                (None, _) => "pending".to_string(),
+            };
+            println!("{:<9}{:<40}{}", migration.version, migration.name, applied_at);
+        }
+        return Ok(());
+    }
+
+    let applied = migrations::migrate(db_pool).await
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    if applied.is_empty() {
+        println!("Database schema is up to date");
+    }
+    for migration in applied {
+        println!("Applied migration {} ({})", migration.version, migration.name);
+    }
+
+    Ok(())
+}
+
+async fn run_prune_command(db_pool: &DbPool, policy: &RetentionPolicy, dry_run: bool) -> std::io::Result<()> {
+    let report = if dry_run {
+        retention::pending(db_pool, policy).await
+    } else {
+        retention::prune(db_pool, policy).await
+    }.map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    println!("{} {} check results older than {} days", verb, report.check_results, policy.raw_days);
+    println!("{} {} legacy response time samples older than {} days", verb, report.legacy_samples, policy.raw_days);
+    println!("{} {} hourly and {} daily rollups older than {} months", verb, report.hourly_rollups, report.daily_rollups, policy.rollup_months);
+    match policy.incident_days {
+        Some(days) => println!("{} {} resolved incidents older than {} days", verb, report.incidents, days),
+        None => println!("Keeping all incidents")
+    }
+
+    Ok(())
+}
+
+async fn run_update_incident_command(db_pool: &DbPool, incident: i32, status: &str, message: &str, impact: Option<&str>) -> std::io::Result<()> {
+    let status: IncidentStatus = status.parse()
+        .map_err(|e: MonitoringError| std::io::Error::other(e.to_string()))?;
+
+    if let Some(impact) = impact {
+        let impact: IncidentImpact = impact.parse()
+            .map_err(|e: MonitoringError| std::io::Error::other(e.to_string()))?;
+        db_pool.set_incident_impact(incident, impact).await
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+    }
+
+    let update = db_pool.post_incident_update(incident, status, message).await
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    println!(
+        "Posted update {} to incident {}: {} at {}",
+        update.id,
+        update.incident_id,
+        update.status.label(),
+        update.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+
+    Ok(())
+}
+
+async fn run_schedule_maintenance_command(
+    db_pool: &DbPool,
+    policy: &RetentionPolicy,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    description: &str,
+    services: &[String],
+) -> std::io::Result<()> {
+    if end <= start {
+        return Err(std::io::Error::other("Maintenance must end after it starts"));
+    }
+
+    let maintenance = db_pool.schedule_maintenance(description, start, end, services, policy.rollup_floor()).await
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    println!(
+        "Scheduled maintenance {} from {} to {} for {}",
+        maintenance.id,
+        maintenance.start_time.format("%Y-%m-%d %H:%M:%S UTC"),
+        maintenance.end_time.format("%Y-%m-%d %H:%M:%S UTC"),
+        maintenance.service_ids.join(", ")
+    );
+
+    Ok(())
+}
+
 async fn run_web_server(app_state: AppState, config: &Config) -> std::io::Result<()> {
     HttpServer::new(move || {
         let app_state = app_state.clone();
@@ -561,9 +732,35 @@ async fn run_web_server(app_state: AppState, config: &Config) -> std::io::Result
             .app_data(web::Data::new(app_state))
             .service(web::resource("/").to(index_service))
             .service(web::resource("/service/{service_id}").to(service_detail_service))
+            .service(web::resource("/service/{service_id}/feed.rss").to(feed::service_rss_feed))
+            .service(web::resource("/service/{service_id}/feed.atom").to(feed::service_atom_feed))
+            .service(web::resource("/service/{service_id}/favicon.{extension}").to(favicon::service_favicon))
+            .service(web::resource("/history").to(history_service))
+            .service(web::resource("/incidents/{incident_id}").to(incident_service))
+            .service(web::resource("/feed.rss").to(feed::rss_feed))
+            .service(web::resource("/feed.atom").to(feed::atom_feed))
+            .service(
+                web::scope("/api/v1")
+                    .service(web::resource("/status").to(api::status_api))
+                    .service(web::resource("/services").to(api::services_api))
+                    .service(web::resource("/services/{service_id}").to(api::service_detail_api))
+                    .service(web::resource("/incidents").to(api::incidents_api))
+            )
+            .service(
+                web::scope("/api/v2")
+                    .service(web::resource("/summary.json").to(atlassian::summary_api))
+                    .service(web::resource("/status.json").to(atlassian::status_api))
+                    .service(web::resource("/components.json").to(atlassian::components_api))
+                    .service(web::resource("/incidents/unresolved.json").to(atlassian::unresolved_incidents_api))
+            )
+            .service(web::resource("/badge/overall.svg").to(badge::overall_badge))
+            .service(web::resource("/badge/{service_id}/status.svg").to(badge::status_badge))
+            .service(web::resource("/badge/{service_id}/uptime/{window}").to(badge::uptime_badge))
             .service(web::resource("/favicons/{filename}").to(favicons_service))
-            .service(web::resource("/favicon.ico").to(favicon_service))
+            .service(web::resource("/favicon.{extension}").to(favicon::overall_favicon))
             .service(web::resource("/robots.txt").to(robots_service))
+            .service(web::resource("/events").to(events::events_service))
+            .service(web::resource("/metrics").to(metrics::metrics_service))
             .service(web::resource("/ping").to(ping_service))
     })
     .workers(config.workers as usize)
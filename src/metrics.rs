@@ -0,0 +1,211 @@
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH}
+};
+
+use actix_web::{web, HttpResponse, Result};
+use lazy_static::lazy_static;
+use moka::future::Cache;
+
+use crate::{
+    AppState,
+    status::uptimes
+};
+
+
+lazy_static! {
+    static ref START_TIME: f64 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default();
+}
+
+pub struct MeteredCache<V> {
+    name: &'static str,
+    cache: Cache<String, V>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<V: Clone + Send + Sync + 'static> MeteredCache<V> {
+    pub fn new(name: &'static str, cache: Cache<String, V>) -> Self {
+        Self {
+            name,
+            cache,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub async fn get(&self, key: &str) -> Option<V> {
+        let value = self.cache.get(key).await;
+        let counter = if value.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    pub async fn insert(&self, key: String, value: V) {
+        self.cache.insert(key, value).await;
+    }
+
+    async fn stats(&self) -> CacheStats {
+        self.cache.run_pending_tasks().await;
+        CacheStats {
+            name: self.name,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.cache.entry_count(),
+        }
+    }
+}
+
+struct CacheStats {
+    name: &'static str,
+    hits: u64,
+    misses: u64,
+    entries: u64,
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+}
+
+pub fn init() {
+    lazy_static::initialize(&START_TIME);
+}
+
+pub async fn metrics_service(data: web::Data<AppState>) -> Result<HttpResponse> {
+    let (services, incidents) = tokio::join!(
+        data.db.list_services(),
+        data.db.list_incidents(false)
+    );
+    let database_up = services.is_ok() && incidents.is_ok();
+    let services = services.unwrap_or_default();
+    let incidents = incidents.unwrap_or_default();
+
+    let mut out = String::new();
+
+    family(&mut out, "statuspage_database_up", "gauge", "Whether the last database query for metrics succeeded.");
+    let _ = writeln!(out, "statuspage_database_up {}", database_up as u8);
+
+    family(&mut out, "statuspage_service_up", "gauge", "Whether the service is currently online.");
+    for service in &services {
+        let _ = writeln!(
+            out,
+            "statuspage_service_up{{service=\"{}\",name=\"{}\"}} {}",
+            escape_label(&service.id),
+            escape_label(&service.name),
+            service.is_online as u8
+        );
+    }
+
+    family(&mut out, "statuspage_service_region_up", "gauge", "Whether the latest check from the region succeeded.");
+    for service in &services {
+        for region in &service.checks.regions {
+            let Some(up) = region.latest_up else { continue };
+            let _ = writeln!(
+                out,
+                "statuspage_service_region_up{{service=\"{}\",region=\"{}\"}} {}",
+                escape_label(&service.id),
+                escape_label(&region.region),
+                up as u8
+            );
+        }
+    }
+
+    family(&mut out, "statuspage_service_response_time_milliseconds", "gauge", "Latest recorded response time, 0 when the last check failed.");
+    for service in &services {
+        let latest = service.checks.latest_response_ms.unwrap_or(0).max(0);
+        let _ = writeln!(
+            out,
+            "statuspage_service_response_time_milliseconds{{service=\"{}\"}} {}",
+            escape_label(&service.id),
+            latest
+        );
+    }
+
+    family(&mut out, "statuspage_service_uptime_ratio", "gauge", "Share of successful checks within the window.");
+    for service in &services {
+        let uptime = uptimes(&service.checks);
+        for (window, value) in [
+            ("24h", uptime.last_24h),
+            ("7d", uptime.last_7d),
+            ("30d", uptime.last_30d),
+            ("90d", uptime.last_90d),
+        ] {
+            let _ = writeln!(
+                out,
+                "statuspage_service_uptime_ratio{{service=\"{}\",window=\"{}\"}} {}",
+                escape_label(&service.id),
+                window,
+                value / 100.0
+            );
+        }
+    }
+
+    family(&mut out, "statuspage_open_incidents", "gauge", "Number of unresolved incidents per service.");
+    for service in &services {
+        let open = incidents.iter().filter(|i| i.service_id == service.id).count();
+        let _ = writeln!(
+            out,
+            "statuspage_open_incidents{{service=\"{}\"}} {}",
+            escape_label(&service.id),
+            open
+        );
+    }
+
+    let caches = [
+        data.index_cache.stats().await,
+        data.service_cache.stats().await,
+        data.api_cache.stats().await,
+        data.badge_cache.stats().await,
+        data.static_cache.stats().await,
+    ];
+
+    family(&mut out, "statuspage_cache_hits", "counter", "Number of cache lookups that returned an entry.");
+    for cache in &caches {
+        let _ = writeln!(out, "statuspage_cache_hits_total{{cache=\"{}\"}} {}", cache.name, cache.hits);
+    }
+
+    family(&mut out, "statuspage_cache_misses", "counter", "Number of cache lookups that found no entry.");
+    for cache in &caches {
+        let _ = writeln!(out, "statuspage_cache_misses_total{{cache=\"{}\"}} {}", cache.name, cache.misses);
+    }
+
+    family(&mut out, "statuspage_cache_entries", "gauge", "Number of entries held by the cache.");
+    for cache in &caches {
+        let _ = writeln!(out, "statuspage_cache_entries{{cache=\"{}\"}} {}", cache.name, cache.entries);
+    }
+
+    let pool = data.db.status();
+
+    family(&mut out, "statuspage_db_pool_max_size", "gauge", "Maximum number of database connections.");
+    let _ = writeln!(out, "statuspage_db_pool_max_size {}", pool.max_size);
+
+    family(&mut out, "statuspage_db_pool_size", "gauge", "Current number of database connections.");
+    let _ = writeln!(out, "statuspage_db_pool_size {}", pool.size);
+
+    family(&mut out, "statuspage_db_pool_available", "gauge", "Number of idle database connections.");
+    let _ = writeln!(out, "statuspage_db_pool_available {}", pool.available);
+
+    family(&mut out, "statuspage_db_pool_waiting", "gauge", "Number of requests waiting for a database connection.");
+    let _ = writeln!(out, "statuspage_db_pool_waiting {}", pool.waiting);
+
+    family(&mut out, "statuspage_process_start_time_seconds", "gauge", "Start time of the process since unix epoch in seconds.");
+    let _ = writeln!(out, "statuspage_process_start_time_seconds {}", *START_TIME);
+
+    out.push_str("# EOF\n");
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/openmetrics-text; version=1.0.0; charset=utf-8")
+        .insert_header(("Cache-Control", "no-store"))
+        .body(out))
+}
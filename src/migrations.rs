@@ -0,0 +1,206 @@
+use chrono::{DateTime, Utc};
+
+use crate::database::{DbPool, MonitoringError};
+
+
+const MIGRATION_LOCK_ID: i64 = 0x7374_6174_7573;
+// Maintenance windows are merged with range_agg, added in PostgreSQL 14.
+const MIN_SERVER_VERSION: i32 = 140000;
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    sql: &'static str,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_services_and_incidents",
+        sql: include_str!("../migrations/0001_create_services_and_incidents.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "create_check_results",
+        sql: include_str!("../migrations/0002_create_check_results.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "create_service_groups",
+        sql: include_str!("../migrations/0003_create_service_groups.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "create_incident_updates",
+        sql: include_str!("../migrations/0004_create_incident_updates.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "add_incident_impact",
+        sql: include_str!("../migrations/0005_add_incident_impact.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "create_maintenances",
+        sql: include_str!("../migrations/0006_create_maintenances.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "add_service_metadata",
+        sql: include_str!("../migrations/0007_add_service_metadata.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "add_check_regions",
+        sql: include_str!("../migrations/0008_add_check_regions.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "create_check_rollups",
+        sql: include_str!("../migrations/0009_create_check_rollups.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "skip_legacy_trims",
+        sql: include_str!("../migrations/0010_skip_legacy_trims.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "add_check_types",
+        sql: include_str!("../migrations/0011_add_check_types.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "create_service_certificates",
+        sql: include_str!("../migrations/0012_create_service_certificates.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "add_check_assertions",
+        sql: include_str!("../migrations/0013_add_check_assertions.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "create_latest_checks",
+        sql: include_str!("../migrations/0014_create_latest_checks.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "unique_open_source_incidents",
+        sql: include_str!("../migrations/0015_unique_open_source_incidents.sql"),
+    },
+    Migration {
+        version: 16,
+        name: "add_check_greeting",
+        sql: include_str!("../migrations/0016_add_check_greeting.sql"),
+    },
+];
+
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub applied_at: Option<DateTime<Utc>>,
+    pub known: bool,
+}
+
+async fn ensure_migrations_table(client: &tokio_postgres::Client) -> Result<(), MonitoringError> {
+    client.batch_execute("
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version BIGINT PRIMARY KEY,
+            name VARCHAR(255) NOT NULL,
+            applied_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+        );
+    ").await.map_err(|e| MonitoringError(e.to_string()))
+}
+
+async fn applied_versions(client: &tokio_postgres::Client) -> Result<Vec<(i64, String, DateTime<Utc>)>, MonitoringError> {
+    let rows = client.query("SELECT version, name, applied_at FROM schema_migrations ORDER BY version", &[])
+        .await.map_err(|e| MonitoringError(e.to_string()))?;
+
+    Ok(rows.iter().map(|row| (row.get(0), row.get(1), row.get(2))).collect())
+}
+
+async fn apply_pending(client: &mut tokio_postgres::Client) -> Result<Vec<&'static Migration>, MonitoringError> {
+    ensure_migrations_table(client).await?;
+
+    let applied: Vec<i64> = applied_versions(client).await?
+        .into_iter()
+        .map(|(version, _, _)| version)
+        .collect();
+
+    let mut newly_applied = Vec::new();
+
+    for migration in MIGRATIONS.iter().filter(|m| !applied.contains(&m.version)) {
+        let transaction = client.transaction().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+
+        transaction.batch_execute(migration.sql).await
+            .map_err(|e| MonitoringError(format!("Migration {} ({}) failed: {}", migration.version, migration.name, e)))?;
+        transaction.execute(
+            "INSERT INTO schema_migrations (version, name) VALUES ($1, $2)",
+            &[&migration.version, &migration.name]
+        ).await.map_err(|e| MonitoringError(e.to_string()))?;
+
+        transaction.commit().await
+            .map_err(|e| MonitoringError(e.to_string()))?;
+        newly_applied.push(migration);
+    }
+
+    Ok(newly_applied)
+}
+
+async fn check_server_version(client: &tokio_postgres::Client) -> Result<(), MonitoringError> {
+    let version: i32 = client.query_one("SELECT current_setting('server_version_num')::INTEGER", &[])
+        .await.map_err(|e| MonitoringError(e.to_string()))?.get(0);
+
+    if version < MIN_SERVER_VERSION {
+        return Err(MonitoringError(format!(
+            "PostgreSQL {} or newer is required, found {}.{}",
+            MIN_SERVER_VERSION / 10000, version / 10000, version % 10000
+        )));
+    }
+
+    Ok(())
+}
+
+pub async fn migrate(pool: &DbPool) -> Result<Vec<&'static Migration>, MonitoringError> {
+    let mut client = pool.client().await?;
+    check_server_version(&client).await?;
+
+    client.execute("SELECT pg_advisory_lock($1)", &[&MIGRATION_LOCK_ID])
+        .await.map_err(|e| MonitoringError(e.to_string()))?;
+
+    let result = apply_pending(&mut client).await;
+
+    client.execute("SELECT pg_advisory_unlock($1)", &[&MIGRATION_LOCK_ID])
+        .await.map_err(|e| MonitoringError(e.to_string()))?;
+
+    result
+}
+
+pub async fn migration_status(pool: &DbPool) -> Result<Vec<MigrationStatus>, MonitoringError> {
+    let client = pool.client().await?;
+    ensure_migrations_table(&client).await?;
+
+    let applied = applied_versions(&client).await?;
+
+    let mut status: Vec<MigrationStatus> = MIGRATIONS.iter().map(|migration| MigrationStatus {
+        version: migration.version,
+        name: migration.name.to_string(),
+        applied_at: applied.iter()
+            .find(|(version, _, _)| *version == migration.version)
+            .map(|(_, _, applied_at)| *applied_at),
+        known: true,
+    }).collect();
+
+    status.extend(applied.into_iter()
+        .filter(|(version, _, _)| !MIGRATIONS.iter().any(|m| m.version == *version))
+        .map(|(version, name, applied_at)| MigrationStatus {
+            version,
+            name,
+            applied_at: Some(applied_at),
+            known: false,
+        }));
+
+    Ok(status)
+}
@@ -0,0 +1,416 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant}
+};
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    task::JoinSet,
+    time::{interval, timeout, MissedTickBehavior}
+};
+use tokio_rustls::{
+    client::TlsStream,
+    rustls::{
+        client::{
+            danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+            WebPkiServerVerifier
+        },
+        pki_types::{CertificateDer, ServerName, UnixTime},
+        ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme
+    },
+    TlsConnector
+};
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+use crate::{
+    assertions::{self, Assertion},
+    database::{Certificate, CheckTarget, CheckType, DbPool, IncidentImpact}
+};
+
+
+const MAX_BANNER_BYTES: u64 = 4096;
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+const CERTIFICATE_REFRESH: Duration = Duration::from_secs(3600);
+const CERTIFICATE_SOURCE: &str = "certificate";
+
+#[derive(Debug, Clone)]
+pub struct MonitorConfig {
+    pub interval: Duration,
+    pub timeout: Duration,
+    pub region: String,
+}
+
+#[derive(Clone)]
+struct Checker {
+    http: reqwest::Client,
+    tls: TlsConnector,
+    verifier: Arc<WebPkiServerVerifier>,
+    timeout: Duration,
+    certificates_checked: Arc<Mutex<HashMap<String, Instant>>>,
+    assertions: Arc<Mutex<HashMap<String, ParsedAssertions>>>,
+}
+
+type ParsedAssertions = (Value, Result<Arc<[Assertion]>, String>);
+
+impl Checker {
+    fn certificate_due(&self, service_id: &str) -> bool {
+        let Ok(checked) = self.certificates_checked.lock() else { return false };
+        checked.get(service_id).is_none_or(|at| at.elapsed() >= CERTIFICATE_REFRESH)
+    }
+
+    fn certificate_checked(&self, service_id: &str) {
+        if let Ok(mut checked) = self.certificates_checked.lock() {
+            checked.insert(service_id.to_string(), Instant::now());
+        }
+    }
+
+    fn assertions(&self, target: &CheckTarget) -> Result<Arc<[Assertion]>, String> {
+        let Ok(mut cache) = self.assertions.lock() else {
+            return assertions::parse(&target.assertions).map(Arc::from);
+        };
+
+        match cache.get(&target.service_id) {
+            Some((source, parsed)) if *source == target.assertions => parsed.clone(),
+            _ => {
+                let parsed = assertions::parse(&target.assertions).map(Arc::from);
+                cache.insert(target.service_id.clone(), (target.assertions.clone(), parsed.clone()));
+                parsed
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CapturingVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    chain: Mutex<Vec<CertificateDer<'static>>>,
+}
+
+impl CapturingVerifier {
+    fn chain(&self) -> Vec<CertificateDer<'static>> {
+        self.chain.lock().map(|chain| chain.clone()).unwrap_or_default()
+    }
+}
+
+impl ServerCertVerifier for CapturingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+        if let Ok(mut chain) = self.chain.lock() {
+            *chain = std::iter::once(end_entity).chain(intermediates)
+                .map(|certificate| certificate.clone().into_owned())
+                .collect();
+        }
+
+        self.inner.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+struct CheckOutcome {
+    response_ms: Option<i32>,
+    error: Option<String>,
+}
+
+fn describe(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+fn socket_address(target: &CheckTarget) -> Result<(String, u16), String> {
+    let url = reqwest::Url::parse(&target.server_url)
+        .map_err(|e| format!("Invalid server URL: {}", e))?;
+    let host = url.host_str().ok_or("Server URL has no host")?;
+    let port = url.port_or_known_default().ok_or("Server URL has no port")?;
+
+    Ok((host.trim_start_matches('[').trim_end_matches(']').to_string(), port))
+}
+
+async fn connect(target: &CheckTarget) -> Result<(TcpStream, String), String> {
+    let (host, port) = socket_address(target)?;
+    let stream = TcpStream::connect((host.as_str(), port)).await
+        .map_err(|e| format!("Connection failed: {}", e))?;
+
+    Ok((stream, host))
+}
+
+fn request_error(error: reqwest::Error) -> String {
+    if error.is_timeout() { "Timed out".to_string() } else { describe(&error) }
+}
+
+async fn read_body(mut response: reqwest::Response) -> Result<String, String> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(request_error)? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= MAX_BODY_BYTES {
+            body.truncate(MAX_BODY_BYTES);
+            break;
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+async fn check_http(checker: &Checker, target: &CheckTarget) -> Result<(), String> {
+    let assertions = checker.assertions(target)?;
+    let response = checker.http.get(&target.server_url).send().await
+        .map_err(request_error)?;
+
+    let (status, headers) = (response.status(), response.headers().clone());
+    let body = if assertions::needs_body(&assertions) { read_body(response).await? } else { String::new() };
+
+    assertions::check(&assertions, status, &headers, &body)
+}
+
+async fn check_tcp(target: &CheckTarget) -> Result<(), String> {
+    connect(target).await.map(|_| ())
+}
+
+async fn handshake(tls: &TlsConnector, target: &CheckTarget) -> Result<TlsStream<TcpStream>, String> {
+    let (stream, host) = connect(target).await?;
+    let server_name = ServerName::try_from(host)
+        .map_err(|e| format!("Invalid server name: {}", e))?;
+
+    tls.connect(server_name, stream).await
+        .map_err(|e| format!("TLS handshake failed: {}", describe(&e)))
+}
+
+async fn check_tls(checker: &Checker, target: &CheckTarget) -> Result<(), String> {
+    handshake(&checker.tls, target).await.map(|_| ())
+}
+
+async fn read_banner_line(stream: &mut BufReader<TcpStream>) -> Result<String, String> {
+    let mut line = String::new();
+    (&mut *stream).take(MAX_BANNER_BYTES).read_line(&mut line).await
+        .map_err(|e| format!("Failed to read: {}", e))?;
+
+    Ok(line.trim_end().to_string())
+}
+
+async fn check_banner(target: &CheckTarget) -> Result<(), String> {
+    let (stream, _) = connect(target).await?;
+    let mut stream = BufReader::new(stream);
+
+    if target.greeting && target.send.is_some() && read_banner_line(&mut stream).await?.is_empty() {
+        return Err("Connection closed without a greeting".to_string());
+    }
+
+    if let Some(send) = &target.send {
+        stream.get_mut().write_all(format!("{}\r\n", send).as_bytes()).await
+            .map_err(|e| format!("Failed to send: {}", e))?;
+    }
+
+    let line = read_banner_line(&mut stream).await?;
+
+    match &target.expect {
+        Some(expect) if !line.contains(expect.as_str()) => Err(format!("Expected '{}', got '{}'", expect, line)),
+        None if line.is_empty() => Err("Connection closed without a response".to_string()),
+        _ => Ok(())
+    }
+}
+
+async fn check(checker: &Checker, target: &CheckTarget) -> CheckOutcome {
+    let started = Instant::now();
+
+    let result = timeout(checker.timeout, async {
+        match target.check_type {
+            CheckType::Http => check_http(checker, target).await,
+            CheckType::Tcp => check_tcp(target).await,
+            CheckType::Tls => check_tls(checker, target).await,
+            CheckType::Banner => check_banner(target).await,
+        }
+    }).await.unwrap_or_else(|_| Err("Timed out".to_string()));
+
+    match result {
+        Ok(()) => CheckOutcome {
+            response_ms: Some(started.elapsed().as_millis().clamp(1, i32::MAX as u128) as i32),
+            error: None,
+        },
+        Err(error) => CheckOutcome {
+            response_ms: None,
+            error: Some(error),
+        },
+    }
+}
+
+fn uses_tls(target: &CheckTarget) -> bool {
+    match target.check_type {
+        CheckType::Tls => true,
+        CheckType::Http => target.server_url.get(..8).is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://")),
+        CheckType::Tcp | CheckType::Banner => false,
+    }
+}
+
+fn parse_certificate(der: &CertificateDer) -> Result<Certificate, String> {
+    let (_, certificate) = X509Certificate::from_der(der.as_ref())
+        .map_err(|e| format!("Invalid certificate: {}", e))?;
+    let validity = certificate.validity();
+    let timestamp = |seconds: i64| DateTime::<Utc>::from_timestamp(seconds, 0)
+        .ok_or_else(|| "Certificate validity is out of range".to_string());
+
+    Ok(Certificate {
+        subject: certificate.subject().to_string(),
+        issuer: certificate.issuer().to_string(),
+        not_before: timestamp(validity.not_before.timestamp())?,
+        not_after: timestamp(validity.not_after.timestamp())?,
+    })
+}
+
+async fn fetch_certificates(checker: &Checker, target: &CheckTarget) -> Result<Vec<Certificate>, String> {
+    let verifier = Arc::new(CapturingVerifier {
+        inner: checker.verifier.clone(),
+        chain: Mutex::default(),
+    });
+    let tls = TlsConnector::from(Arc::new(
+        ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(verifier.clone())
+            .with_no_client_auth()
+    ));
+
+    let handshake = handshake(&tls, target).await;
+    let chain = verifier.chain();
+    if chain.is_empty() {
+        return Err(handshake.err().unwrap_or_else(|| "Server sent no certificates".to_string()));
+    }
+
+    chain.iter().map(parse_certificate).collect()
+}
+
+async fn check_certificates(db: &DbPool, checker: &Checker, target: &CheckTarget) {
+    if !checker.certificate_due(&target.service_id) {
+        return;
+    }
+
+    let chain = if uses_tls(target) {
+        match timeout(checker.timeout, fetch_certificates(checker, target)).await {
+            Ok(Ok(chain)) => chain,
+            Ok(Err(_)) | Err(_) => return,
+        }
+    } else {
+        Vec::new()
+    };
+
+    let warning = chrono::Duration::days(target.certificate_warning_days as i64);
+    let problem = chain.first()
+        .map(|leaf| leaf.not_after)
+        .filter(|expires_at| *expires_at - Utc::now() < warning)
+        .map(|expires_at| if expires_at <= Utc::now() {
+            format!("TLS certificate expired on {}", expires_at.format("%Y-%m-%d %H:%M UTC"))
+        } else {
+            format!("TLS certificate expires on {}", expires_at.format("%Y-%m-%d %H:%M UTC"))
+        });
+
+    let result = match db.record_certificates(&target.service_id, &chain).await {
+        Ok(()) => db.sync_monitor_incident(
+            &target.service_id,
+            CERTIFICATE_SOURCE,
+            problem.as_deref().map(|description| (description, IncidentImpact::Minor))
+        ).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => checker.certificate_checked(&target.service_id),
+        Err(e) => eprintln!("Failed to record certificates for {}: {}", target.service_id, e),
+    }
+}
+
+async fn run_checks(db: &Arc<DbPool>, checker: &Checker, region: &Arc<str>) {
+    let targets = match db.list_check_targets().await {
+        Ok(targets) => targets,
+        Err(e) => {
+            eprintln!("Monitor database error: {}", e);
+            return;
+        }
+    };
+
+    let mut checks = JoinSet::new();
+    for target in targets {
+        let (db, checker, region) = (db.clone(), checker.clone(), region.clone());
+        checks.spawn(async move {
+            let outcome = check(&checker, &target).await;
+            if let Err(e) = db.record_check(&target.service_id, &region, outcome.response_ms, outcome.error.as_deref()).await {
+                eprintln!("Failed to record check for {}: {}", target.service_id, e);
+            }
+            check_certificates(&db, &checker, &target).await;
+        });
+    }
+
+    while checks.join_next().await.is_some() {}
+}
+
+pub fn spawn_monitor(db: Arc<DbPool>, config: MonitorConfig) -> Result<(), Box<dyn Error>> {
+    let http = reqwest::Client::builder()
+        .timeout(config.timeout)
+        .user_agent(concat!("statuspage/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+
+    let roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let verifier = WebPkiServerVerifier::builder(Arc::new(roots)).build()?;
+    let tls = TlsConnector::from(Arc::new(
+        ClientConfig::builder()
+            .with_webpki_verifier(verifier.clone())
+            .with_no_client_auth()
+    ));
+
+    let checker = Checker {
+        http,
+        tls,
+        verifier,
+        timeout: config.timeout,
+        certificates_checked: Arc::default(),
+        assertions: Arc::default(),
+    };
+    let region: Arc<str> = config.region.into();
+
+    tokio::spawn(async move {
+        let mut ticker = interval(config.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            run_checks(&db, &checker, &region).await;
+        }
+    });
+
+    Ok(())
+}
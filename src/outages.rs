@@ -0,0 +1,111 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::time::{interval, MissedTickBehavior};
+
+use crate::{
+    database::{DbPool, IncidentImpact, OutageState},
+    status::REGION_FRESHNESS_MINUTES
+};
+
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const INCIDENT_SOURCE: &str = "monitor";
+
+#[derive(Debug, Clone, Copy)]
+pub struct OutagePolicy {
+    pub threshold: u32,
+    pub manual_resolution: bool,
+}
+
+// An outage is reported once an incident from the monitor is open or was
+// started after the service was last seen up (or first checked, if never up).
+fn reported(state: &OutageState) -> bool {
+    state.open || matches!((state.last_reported, state.outage_since), (Some(reported), Some(since)) if reported > since)
+}
+
+async fn detect_outages(db: &DbPool, policy: OutagePolicy) {
+    let states = match db.list_outage_states(policy.threshold, REGION_FRESHNESS_MINUTES, INCIDENT_SOURCE).await {
+        Ok(states) => states,
+        Err(e) => {
+            eprintln!("Outage detection error: {}", e);
+            return;
+        }
+    };
+
+    for state in states {
+        let problem = if state.confirmed && !reported(&state) {
+            let checks = if policy.threshold == 1 { "check".to_string() } else { format!("{} consecutive checks", policy.threshold) };
+            Some(match &state.error {
+                Some(error) => format!("{} failed {}: {}", state.name, checks, error),
+                None => format!("{} failed {}", state.name, checks),
+            })
+        } else if state.recovered && state.open && !policy.manual_resolution {
+            None
+        } else {
+            continue;
+        };
+
+        let problem = problem.as_deref().map(|description| (description, IncidentImpact::Major));
+        if let Err(e) = db.sync_monitor_incident(&state.service_id, INCIDENT_SOURCE, problem).await {
+            eprintln!("Failed to sync incident for {}: {}", state.service_id, e);
+        }
+    }
+}
+
+pub fn spawn_detector(db: Arc<DbPool>, policy: OutagePolicy) {
+    tokio::spawn(async move {
+        let mut ticker = interval(POLL_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            detect_outages(&db, policy).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{DateTime, Duration, Utc};
+
+    fn state(outage_since: Option<DateTime<Utc>>) -> OutageState {
+        OutageState {
+            service_id: "api".to_string(),
+            name: "API".to_string(),
+            confirmed: true,
+            recovered: false,
+            error: None,
+            outage_since,
+            last_reported: None,
+            open: false,
+        }
+    }
+
+    #[test]
+    fn a_service_that_was_never_up_is_reported_once() {
+        let first_checked = Utc::now() - Duration::hours(1);
+        let mut state = state(Some(first_checked));
+        assert!(!reported(&state));
+
+        state.last_reported = Some(first_checked + Duration::minutes(5));
+        assert!(reported(&state));
+    }
+
+    #[test]
+    fn incidents_from_an_earlier_outage_do_not_count() {
+        let mut state = state(Some(Utc::now() - Duration::minutes(10)));
+        state.last_reported = Some(Utc::now() - Duration::days(1));
+        assert!(!reported(&state));
+    }
+
+    #[test]
+    fn an_open_incident_is_always_reported() {
+        let mut state = state(None);
+        assert!(!reported(&state));
+
+        state.open = true;
+        assert!(reported(&state));
+    }
+}
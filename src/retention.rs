@@ -0,0 +1,156 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Months, NaiveTime, Utc};
+use tokio::time::{interval, MissedTickBehavior};
+
+use crate::database::{DbPool, MonitoringError};
+
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+const BATCH_SIZE: i64 = 10_000;
+const LEGACY_SAMPLES_PER_DAY: i64 = 1440;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub raw_days: u32,
+    pub rollup_months: u32,
+    pub incident_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PruneReport {
+    pub check_results: u64,
+    pub legacy_samples: u64,
+    pub hourly_rollups: u64,
+    pub daily_rollups: u64,
+    pub incidents: u64,
+}
+
+impl PruneReport {
+    pub fn total(&self) -> u64 {
+        self.check_results + self.legacy_samples + self.hourly_rollups + self.daily_rollups + self.incidents
+    }
+}
+
+struct Cutoffs {
+    raw: DateTime<Utc>,
+    rollups: DateTime<Utc>,
+    incidents: Option<DateTime<Utc>>,
+    legacy_samples: i32,
+}
+
+impl RetentionPolicy {
+    fn cutoffs(&self) -> Cutoffs {
+        let now = Utc::now();
+        Cutoffs {
+            raw: now - chrono::Duration::days(self.raw_days as i64),
+            rollups: now.checked_sub_months(Months::new(self.rollup_months)).unwrap_or(DateTime::<Utc>::MIN_UTC),
+            incidents: self.incident_days.map(|days| now - chrono::Duration::days(days as i64)),
+            legacy_samples: (self.raw_days as i64 * LEGACY_SAMPLES_PER_DAY).min(i32::MAX as i64) as i32,
+        }
+    }
+
+    pub fn rollup_floor(&self) -> DateTime<Utc> {
+        let raw_cutoff = self.cutoffs().raw.date_naive();
+        raw_cutoff.succ_opt().unwrap_or(raw_cutoff).and_time(NaiveTime::MIN).and_utc()
+    }
+}
+
+const CHECK_RESULTS: (&str, &str) = ("check_results", "checked_at < $1");
+const HOURLY_ROLLUPS: (&str, &str) = ("check_rollups_hourly", "hour < $1");
+const DAILY_ROLLUPS: (&str, &str) = ("check_rollups_daily", "day < ($1::TIMESTAMPTZ AT TIME ZONE 'UTC')::DATE");
+const INCIDENTS: (&str, &str) = ("incidents", "end_time < $1");
+
+async fn count(client: &tokio_postgres::Client, (table, condition): (&str, &str), cutoff: &DateTime<Utc>) -> Result<u64, MonitoringError> {
+    let count: i64 = client.query_one(&format!("SELECT COUNT(*) FROM {} WHERE {}", table, condition), &[cutoff])
+        .await.map_err(|e| MonitoringError(e.to_string()))?.get(0);
+
+    Ok(count as u64)
+}
+
+async fn delete_in_batches(client: &tokio_postgres::Client, (table, condition): (&str, &str), cutoff: &DateTime<Utc>) -> Result<u64, MonitoringError> {
+    let query = format!(
+        "DELETE FROM {table} WHERE ctid = ANY(ARRAY(SELECT ctid FROM {table} WHERE {condition} LIMIT $2))",
+        table = table,
+        condition = condition
+    );
+
+    let mut total = 0;
+    loop {
+        let deleted = client.execute(&query, &[cutoff, &BATCH_SIZE])
+            .await.map_err(|e| MonitoringError(e.to_string()))?;
+        total += deleted;
+        if deleted < BATCH_SIZE as u64 {
+            return Ok(total);
+        }
+    }
+}
+
+pub async fn pending(pool: &DbPool, policy: &RetentionPolicy) -> Result<PruneReport, MonitoringError> {
+    let client = pool.client().await?;
+    let cutoffs = policy.cutoffs();
+
+    let legacy_samples: i64 = client.query_one(
+        "SELECT COALESCE(SUM(array_length(response_times, 1) - $1), 0)::BIGINT FROM services WHERE array_length(response_times, 1) > $1",
+        &[&cutoffs.legacy_samples]
+    ).await.map_err(|e| MonitoringError(e.to_string()))?.get(0);
+
+    Ok(PruneReport {
+        check_results: count(&client, CHECK_RESULTS, &cutoffs.raw).await?,
+        legacy_samples: legacy_samples as u64,
+        hourly_rollups: count(&client, HOURLY_ROLLUPS, &cutoffs.rollups).await?,
+        daily_rollups: count(&client, DAILY_ROLLUPS, &cutoffs.rollups).await?,
+        incidents: match &cutoffs.incidents {
+            Some(cutoff) => count(&client, INCIDENTS, cutoff).await?,
+            None => 0
+        },
+    })
+}
+
+pub async fn prune(pool: &DbPool, policy: &RetentionPolicy) -> Result<PruneReport, MonitoringError> {
+    let client = pool.client().await?;
+    let cutoffs = policy.cutoffs();
+
+    let legacy_samples: i64 = client.query_one("
+        WITH trimmed AS (
+            UPDATE services s
+            SET response_times = s.response_times[1:$1]
+            FROM services old
+            WHERE old.id = s.id AND array_length(old.response_times, 1) > $1
+            RETURNING array_length(old.response_times, 1) - $1 AS removed
+        )
+        SELECT COALESCE(SUM(removed), 0)::BIGINT FROM trimmed",
+        &[&cutoffs.legacy_samples]
+    ).await.map_err(|e| MonitoringError(e.to_string()))?.get(0);
+
+    Ok(PruneReport {
+        check_results: delete_in_batches(&client, CHECK_RESULTS, &cutoffs.raw).await?,
+        legacy_samples: legacy_samples as u64,
+        hourly_rollups: delete_in_batches(&client, HOURLY_ROLLUPS, &cutoffs.rollups).await?,
+        daily_rollups: delete_in_batches(&client, DAILY_ROLLUPS, &cutoffs.rollups).await?,
+        incidents: match &cutoffs.incidents {
+            Some(cutoff) => delete_in_batches(&client, INCIDENTS, cutoff).await?,
+            None => 0
+        },
+    })
+}
+
+pub fn spawn_pruner(db: Arc<DbPool>, policy: RetentionPolicy) {
+    tokio::spawn(async move {
+        let mut ticker = interval(PRUNE_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+
+            match prune(&db, &policy).await {
+                Ok(report) if report.total() > 0 => println!(
+                    "Pruned {} check results, {} legacy samples, {} hourly rollups, {} daily rollups and {} incidents",
+                    report.check_results, report.legacy_samples, report.hourly_rollups, report.daily_rollups, report.incidents
+                ),
+                Ok(_) => {},
+                Err(e) => eprintln!("Retention error: {}", e)
+            }
+        }
+    });
+}
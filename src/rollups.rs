@@ -0,0 +1,31 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DurationRound, TimeDelta, Utc};
+use tokio::time::{interval, MissedTickBehavior};
+
+use crate::database::DbPool;
+
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+pub fn spawn_refresher(db: Arc<DbPool>) {
+    tokio::spawn(async move {
+        let mut ticker = interval(REFRESH_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut percentiles_hour = None;
+
+        loop {
+            ticker.tick().await;
+
+            // Daily percentiles need a raw scan, so they only refresh once an hour.
+            let hour = Utc::now().duration_trunc(TimeDelta::hours(1)).ok();
+            let percentiles = hour != percentiles_hour;
+
+            match db.refresh_rollups(None, percentiles).await {
+                Ok(()) if percentiles => percentiles_hour = hour,
+                Ok(()) => {},
+                Err(e) => eprintln!("Rollup refresh error: {}", e)
+            }
+        }
+    });
+}
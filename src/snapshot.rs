@@ -0,0 +1,90 @@
+use std::sync::{Arc, RwLock};
+
+use actix_web::{error::InternalError, HttpResponse};
+use chrono::{DateTime, Utc};
+
+use crate::database::{DbPool, Incident, Maintenance, MonitoringError, Service, ServiceGroup};
+
+
+#[derive(Debug)]
+pub struct Snapshot {
+    pub services: Vec<Service>,
+    pub groups: Vec<ServiceGroup>,
+    pub incidents: Vec<Incident>,
+    pub maintenances: Vec<Maintenance>,
+    pub taken_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoadedSnapshot {
+    pub snapshot: Arc<Snapshot>,
+    pub stale: bool,
+}
+
+impl LoadedSnapshot {
+    pub fn stale_since(&self) -> Option<DateTime<Utc>> {
+        self.stale.then_some(self.snapshot.taken_at)
+    }
+}
+
+#[derive(Default)]
+pub struct SnapshotStore {
+    last_good: RwLock<Option<Arc<Snapshot>>>,
+}
+
+impl SnapshotStore {
+    pub async fn fetch(db: &DbPool) -> Result<Snapshot, MonitoringError> {
+        let (services, groups, mut incidents, mut maintenances) = tokio::try_join!(
+            db.list_services(),
+            db.list_service_groups(),
+            db.list_incidents(false),
+            db.list_maintenances()
+        )?;
+        incidents.retain(|incident| services.iter().any(|service| service.id == incident.service_id));
+        for maintenance in &mut maintenances {
+            maintenance.service_ids.retain(|id| services.iter().any(|service| &service.id == id));
+        }
+        maintenances.retain(|maintenance| !maintenance.service_ids.is_empty());
+
+        Ok(Snapshot {
+            services,
+            groups,
+            incidents,
+            maintenances,
+            taken_at: Utc::now(),
+        })
+    }
+
+    pub async fn load(&self, db: &DbPool) -> Option<LoadedSnapshot> {
+        match Self::fetch(db).await {
+            Ok(snapshot) => {
+                let snapshot = Arc::new(snapshot);
+                if let Ok(mut last_good) = self.last_good.write() {
+                    *last_good = Some(snapshot.clone());
+                }
+                Some(LoadedSnapshot { snapshot, stale: false })
+            },
+            Err(e) => {
+                let last_good = self.last_good.read().ok().and_then(|last_good| last_good.clone());
+                match &last_good {
+                    Some(snapshot) => eprintln!("Database error, serving snapshot from {}: {}", snapshot.taken_at, e),
+                    None => eprintln!("Database error, no snapshot available: {}", e)
+                }
+                last_good.map(|snapshot| LoadedSnapshot { snapshot, stale: true })
+            }
+        }
+    }
+}
+
+pub const RETRY_AFTER_SECS: &str = "30";
+
+pub fn unavailable() -> actix_web::Error {
+    const MESSAGE: &str = "Status data is temporarily unavailable";
+
+    let response = HttpResponse::ServiceUnavailable()
+        .content_type("text/plain; charset=utf-8")
+        .insert_header(("Cache-Control", "no-store"))
+        .insert_header(("Retry-After", RETRY_AFTER_SECS))
+        .body(MESSAGE);
+    InternalError::from_response(MESSAGE, response).into()
+}
@@ -0,0 +1,621 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::database::{
+    CheckStats, CheckWindow, DailyChecks, Incident, IncidentImpact, IncidentStatus, IncidentUpdate, Maintenance, RegionChecks, Service,
+    ServiceGroup
+};
+
+
+pub const MAX_DAYS: usize = 90;
+const GRAPH_DAYS: usize = 30;
+pub const REGION_FRESHNESS_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverallStatus {
+    Up,
+    Degraded,
+    Partial,
+    Down,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusSegment {
+    pub name: &'static str,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Uptimes {
+    pub last_24h: f64,
+    pub last_7d: f64,
+    pub last_30d: f64,
+    pub last_90d: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceSummary {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub public_url: Option<String>,
+    pub tags: Vec<String>,
+    pub is_online: bool,
+    pub state: OverallStatus,
+    pub group_id: Option<String>,
+    pub uptime: Uptimes,
+    pub status: Vec<StatusSegment>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupSummary {
+    pub id: String,
+    pub name: String,
+    pub status: OverallStatus,
+    pub uptime: Uptimes,
+    pub services: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IncidentSummary {
+    pub id: i32,
+    pub service_id: String,
+    pub service_name: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub duration_seconds: Option<i64>,
+    pub description: String,
+    pub impact: IncidentImpact,
+    pub status: IncidentStatus,
+    pub updates: Vec<IncidentUpdate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceSummary {
+    pub id: i32,
+    pub description: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub in_progress: bool,
+    pub service_ids: Vec<String>,
+    pub service_names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusOverview {
+    pub status: OverallStatus,
+    pub uptime: Uptimes,
+    pub services: Vec<ServiceSummary>,
+    pub groups: Vec<GroupSummary>,
+    pub incidents: Vec<IncidentSummary>,
+    pub maintenances: Vec<MaintenanceSummary>,
+    pub stale_since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyBucket {
+    pub date: NaiveDate,
+    pub samples: usize,
+    pub down: usize,
+    pub maintenance: usize,
+    pub uptime: f64,
+    pub avg_response_time: Option<u32>,
+    pub p95_response_time: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ResponseStats {
+    pub avg: u32,
+    pub min: u32,
+    pub max: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceDetail {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub public_url: Option<String>,
+    pub tags: Vec<String>,
+    pub is_online: bool,
+    pub state: OverallStatus,
+    pub group_id: Option<String>,
+    pub uptime: Uptimes,
+    pub status: Vec<StatusSegment>,
+    pub daily: Vec<DailyBucket>,
+    pub daily_averages: Vec<u32>,
+    pub response_time: ResponseStats,
+    pub graph_points: Vec<u32>,
+    pub regions: Vec<RegionSummary>,
+    pub certificate: Option<CertificateSummary>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CertificateSummary {
+    pub expires_at: DateTime<Utc>,
+    pub days_left: i64,
+    pub expiring: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegionSummary {
+    pub region: String,
+    pub is_up: Option<bool>,
+    pub fresh: bool,
+    pub last_checked: Option<DateTime<Utc>>,
+    pub uptime: Uptimes,
+}
+
+pub fn calculate_uptime(window: &CheckWindow) -> f64 {
+    if window.samples == 0 {
+        return 100.0;
+    }
+
+    (window.up as f64 / window.samples as f64) * 100.0
+}
+
+pub fn uptimes(checks: &CheckStats) -> Uptimes {
+    Uptimes {
+        last_24h: calculate_uptime(&checks.last_24h),
+        last_7d: calculate_uptime(&checks.last_7d),
+        last_30d: calculate_uptime(&checks.last_30d),
+        last_90d: calculate_uptime(&checks.last_90d),
+    }
+}
+
+fn is_fresh(region: &RegionChecks) -> bool {
+    let cutoff = Utc::now() - Duration::minutes(REGION_FRESHNESS_MINUTES);
+    region.latest_checked_at.is_some_and(|checked_at| checked_at >= cutoff)
+}
+
+fn region_summary(region: &RegionChecks) -> RegionSummary {
+    RegionSummary {
+        region: region.region.clone(),
+        is_up: region.latest_up,
+        fresh: is_fresh(region),
+        last_checked: region.latest_checked_at,
+        uptime: Uptimes {
+            last_24h: calculate_uptime(&region.last_24h),
+            last_7d: calculate_uptime(&region.last_7d),
+            last_30d: calculate_uptime(&region.last_30d),
+            last_90d: calculate_uptime(&region.last_90d),
+        },
+    }
+}
+
+fn days_back(checks: &CheckStats, days: usize) -> Vec<(NaiveDate, Option<&DailyChecks>)> {
+    let today = Utc::now().date_naive();
+    let by_date: HashMap<NaiveDate, &DailyChecks> = checks.daily.iter()
+        .map(|day| (day.date, day))
+        .collect();
+
+    (0..days).rev()
+        .map(|offset| {
+            let date = today - Duration::days(offset as i64);
+            (date, by_date.get(&date).copied())
+        })
+        .collect()
+}
+
+pub fn status_segments(checks: &CheckStats) -> Vec<StatusSegment> {
+    days_back(checks, MAX_DAYS).into_iter().map(|(_, day)| {
+        let day = match day {
+            Some(day) if day.samples > 0 || day.maintenance > 0 => day,
+            _ => return StatusSegment {
+                name: "no-data",
+                description: "No data".to_string()
+            }
+        };
+
+        if day.samples == 0 {
+            return StatusSegment {
+                name: "maintenance",
+                description: "Scheduled maintenance".to_string()
+            };
+        }
+
+        let down_percentage = (day.down as f64 / day.samples as f64) * 100.0;
+
+        let name = if down_percentage > 50.0 {
+            "down"
+        } else if down_percentage > 25.0 {
+            "partial"
+        } else if day.maintenance > 0 {
+            "maintenance"
+        } else {
+            "up"
+        };
+
+        StatusSegment {
+            name,
+            description: if day.maintenance > 0 {
+                format!("{:.1}% · Scheduled maintenance", 100.0 - down_percentage)
+            } else {
+                format!("{:.1}%", 100.0 - down_percentage)
+            }
+        }
+    }).collect()
+}
+
+fn impact_status(impact: IncidentImpact) -> OverallStatus {
+    match impact {
+        IncidentImpact::None => OverallStatus::Up,
+        IncidentImpact::Minor => OverallStatus::Degraded,
+        IncidentImpact::Major => OverallStatus::Partial,
+        IncidentImpact::Critical => OverallStatus::Down,
+    }
+}
+
+fn reachability(service: &Service) -> OverallStatus {
+    let regions: Vec<&RegionChecks> = service.checks.regions.iter().filter(|region| is_fresh(region)).collect();
+
+    match service.region_quorum {
+        Some(quorum) if !regions.is_empty() => {
+            let down = regions.iter().filter(|region| region.latest_up == Some(false)).count();
+            if down >= quorum as usize {
+                OverallStatus::Down
+            } else if down > 0 {
+                OverallStatus::Partial
+            } else {
+                OverallStatus::Up
+            }
+        },
+        _ if !service.is_online => OverallStatus::Down,
+        _ => OverallStatus::Up
+    }
+}
+
+fn certificate_expiring(service: &Service) -> bool {
+    service.certificate_expires_at
+        .is_some_and(|expires_at| expires_at - Utc::now() < Duration::days(service.certificate_warning_days as i64))
+}
+
+pub fn service_state(service: &Service, incidents: &[Incident]) -> OverallStatus {
+    let reachability = reachability(service);
+    if reachability == OverallStatus::Down {
+        return OverallStatus::Down;
+    }
+
+    let certificate = if certificate_expiring(service) { OverallStatus::Degraded } else { OverallStatus::Up };
+
+    incidents.iter()
+        .filter(|incident| incident.service_id == service.id && incident.end_time.is_none())
+        .map(|incident| impact_status(incident.impact))
+        .fold(reachability.max(certificate), OverallStatus::max)
+}
+
+fn status_of(states: impl Iterator<Item = OverallStatus>) -> OverallStatus {
+    let (total, down, worst) = states.fold((0, 0, OverallStatus::Up), |(total, down, worst), state| {
+        (total + 1, down + (state == OverallStatus::Down) as usize, worst.max(state))
+    });
+
+    match worst {
+        OverallStatus::Down if down == total => OverallStatus::Down,
+        OverallStatus::Down => OverallStatus::Partial,
+        state => state
+    }
+}
+
+pub fn overall_status(services: &[Service], incidents: &[Incident]) -> OverallStatus {
+    status_of(services.iter().map(|s| service_state(s, incidents)))
+}
+
+pub fn services_down(services: &[ServiceSummary]) -> usize {
+    services.iter().filter(|s| s.state == OverallStatus::Down).count()
+}
+
+fn average_uptime<'a>(uptimes: impl Iterator<Item = &'a Uptimes>) -> Uptimes {
+    let (count, totals) = uptimes.fold((0usize, [0.0; 4]), |(count, totals), uptime| {
+        (count + 1, [
+            totals[0] + uptime.last_24h,
+            totals[1] + uptime.last_7d,
+            totals[2] + uptime.last_30d,
+            totals[3] + uptime.last_90d,
+        ])
+    });
+
+    let [last_24h, last_7d, last_30d, last_90d] = if count > 0 {
+        totals.map(|total| total / count as f64)
+    } else {
+        [100.0; 4]
+    };
+
+    Uptimes { last_24h, last_7d, last_30d, last_90d }
+}
+
+pub fn summarize_incident(incident: Incident) -> IncidentSummary {
+    let status = match (incident.updates.last(), incident.end_time) {
+        (Some(update), _) => update.status,
+        (None, Some(_)) => IncidentStatus::Resolved,
+        (None, None) => IncidentStatus::Investigating
+    };
+
+    IncidentSummary {
+        id: incident.id,
+        service_id: incident.service_id,
+        service_name: incident.service_name,
+        start_time: incident.start_time,
+        end_time: incident.end_time,
+        duration_seconds: incident.end_time.map(|end| (end - incident.start_time).num_seconds().max(0)),
+        description: incident.description,
+        impact: incident.impact,
+        status,
+        updates: incident.updates,
+    }
+}
+
+pub fn summarize_maintenance(maintenance: &Maintenance, services: &[Service]) -> MaintenanceSummary {
+    let now = Utc::now();
+
+    MaintenanceSummary {
+        id: maintenance.id,
+        description: maintenance.description.clone(),
+        start_time: maintenance.start_time,
+        end_time: maintenance.end_time,
+        in_progress: maintenance.start_time <= now && now < maintenance.end_time,
+        service_ids: maintenance.service_ids.clone(),
+        service_names: maintenance.service_ids.iter()
+            .map(|id| services.iter().find(|s| &s.id == id).map_or_else(|| id.clone(), |s| s.name.clone()))
+            .collect(),
+    }
+}
+
+pub fn format_duration(seconds: i64) -> String {
+    let (days, hours, minutes) = (seconds / 86400, seconds % 86400 / 3600, seconds % 3600 / 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+pub fn build_overview(services: &[Service], groups: &[ServiceGroup], incidents: Vec<Incident>, maintenances: &[Maintenance]) -> StatusOverview {
+    let summaries: Vec<ServiceSummary> = services.iter().map(|service| ServiceSummary {
+        id: service.id.clone(),
+        name: service.name.clone(),
+        description: service.description.clone(),
+        public_url: service.public_url.clone(),
+        tags: service.tags.clone(),
+        is_online: service.is_online,
+        state: service_state(service, &incidents),
+        group_id: service.group_id.clone(),
+        uptime: uptimes(&service.checks),
+        status: status_segments(&service.checks),
+    }).collect();
+
+    let groups = groups.iter().filter_map(|group| {
+        let members: Vec<&ServiceSummary> = summaries.iter()
+            .filter(|s| s.group_id.as_ref() == Some(&group.id))
+            .collect();
+
+        if members.is_empty() {
+            return None;
+        }
+
+        Some(GroupSummary {
+            id: group.id.clone(),
+            name: group.name.clone(),
+            status: status_of(members.iter().map(|s| s.state)),
+            uptime: average_uptime(members.iter().map(|s| &s.uptime)),
+            services: members.iter().map(|s| s.id.clone()).collect(),
+        })
+    }).collect();
+
+    let status = status_of(summaries.iter().map(|s| s.state));
+    let incidents = incidents.into_iter().map(summarize_incident).collect();
+
+    StatusOverview {
+        status,
+        uptime: average_uptime(summaries.iter().map(|s| &s.uptime)),
+        services: summaries,
+        groups,
+        incidents,
+        maintenances: maintenances.iter().map(|m| summarize_maintenance(m, services)).collect(),
+        stale_since: None,
+    }
+}
+
+fn daily_buckets(checks: &CheckStats) -> Vec<DailyBucket> {
+    checks.daily.iter().rev().map(|day| {
+        let samples = day.samples.max(0) as usize;
+        let down = day.down.max(0) as usize;
+
+        DailyBucket {
+            date: day.date,
+            samples,
+            down,
+            maintenance: day.maintenance.max(0) as usize,
+            uptime: if samples > 0 { ((samples - down) as f64 / samples as f64) * 100.0 } else { 100.0 },
+            avg_response_time: day.avg_response_ms.map(|avg| avg.round() as u32),
+            p95_response_time: day.p95_response_ms.map(|p95| p95.round() as u32),
+        }
+    }).collect()
+}
+
+pub fn build_service_detail(service: &Service, incidents: &[Incident]) -> ServiceDetail {
+    let checks = &service.checks;
+    let daily = daily_buckets(checks);
+
+    let graph_days: Vec<&DailyChecks> = days_back(checks, GRAPH_DAYS).into_iter()
+        .filter_map(|(_, day)| day)
+        .collect();
+
+    let daily_averages: Vec<u32> = graph_days.iter()
+        .filter_map(|day| day.avg_response_ms)
+        .map(|avg| avg.round() as u32)
+        .collect();
+
+    let response_time = ResponseStats {
+        avg: if !daily_averages.is_empty() {
+            let sum: u64 = daily_averages.iter().map(|&x| x as u64).sum();
+            (sum / daily_averages.len() as u64) as u32
+        } else {
+            0
+        },
+        min: graph_days.iter().filter_map(|day| day.min_response_ms).min().unwrap_or(0).max(0) as u32,
+        max: graph_days.iter().filter_map(|day| day.max_response_ms).max().unwrap_or(0).max(0) as u32,
+    };
+
+    ServiceDetail {
+        id: service.id.clone(),
+        name: service.name.clone(),
+        description: service.description.clone(),
+        public_url: service.public_url.clone(),
+        tags: service.tags.clone(),
+        is_online: service.is_online,
+        state: service_state(service, incidents),
+        group_id: service.group_id.clone(),
+        uptime: uptimes(checks),
+        status: status_segments(checks),
+        daily,
+        graph_points: daily_averages.clone(),
+        daily_averages,
+        response_time,
+        regions: checks.regions.iter().map(region_summary).collect(),
+        certificate: service.certificate_expires_at.map(|expires_at| CertificateSummary {
+            expires_at,
+            days_left: (expires_at - Utc::now()).num_days(),
+            expiring: certificate_expiring(service),
+        }),
+        last_error: checks.latest_error.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use OverallStatus::{Degraded, Down, Partial, Up};
+
+    fn service(is_online: bool) -> Service {
+        Service {
+            id: "api".to_string(),
+            name: "API".to_string(),
+            server_url: "https://api.example.com".to_string(),
+            description: None,
+            public_url: None,
+            position: 0,
+            hidden: false,
+            tags: Vec::new(),
+            is_online,
+            region_quorum: None,
+            certificate_warning_days: 14,
+            certificate_expires_at: None,
+            group_id: None,
+            checks: CheckStats::default(),
+        }
+    }
+
+    fn incident(service_id: &str, impact: IncidentImpact, resolved: bool) -> Incident {
+        Incident {
+            id: 1,
+            service_id: service_id.to_string(),
+            service_name: service_id.to_string(),
+            start_time: Utc::now() - Duration::hours(1),
+            end_time: resolved.then(Utc::now),
+            description: "Investigating".to_string(),
+            impact,
+            updates: Vec::new(),
+        }
+    }
+
+    fn region(name: &str, up: bool) -> RegionChecks {
+        RegionChecks {
+            region: name.to_string(),
+            latest_up: Some(up),
+            latest_checked_at: Some(Utc::now()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn service_state_follows_reachability() {
+        assert_eq!(service_state(&service(true), &[]), Up);
+        assert_eq!(service_state(&service(false), &[]), Down);
+    }
+
+    #[test]
+    fn service_state_applies_open_incident_impact() {
+        let service = service(true);
+        assert_eq!(service_state(&service, &[incident("api", IncidentImpact::Minor, false)]), Degraded);
+        assert_eq!(service_state(&service, &[incident("api", IncidentImpact::Major, false)]), Partial);
+        assert_eq!(service_state(&service, &[incident("api", IncidentImpact::Critical, false)]), Down);
+        assert_eq!(service_state(&service, &[incident("api", IncidentImpact::None, false)]), Up);
+    }
+
+    #[test]
+    fn service_state_ignores_resolved_and_unrelated_incidents() {
+        let incidents = [incident("api", IncidentImpact::Critical, true), incident("web", IncidentImpact::Critical, false)];
+        assert_eq!(service_state(&service(true), &incidents), Up);
+    }
+
+    #[test]
+    fn service_state_uses_the_region_quorum() {
+        let mut service = service(false);
+        service.region_quorum = Some(2);
+        service.checks.regions = vec![region("eu", false), region("us", true), region("ap", true)];
+        assert_eq!(service_state(&service, &[]), Partial);
+
+        service.checks.regions[1].latest_up = Some(false);
+        assert_eq!(service_state(&service, &[]), Down);
+    }
+
+    #[test]
+    fn service_state_ignores_stale_regions() {
+        let mut service = service(true);
+        service.region_quorum = Some(1);
+        service.checks.regions = vec![region("eu", true), region("us", false)];
+        service.checks.regions[1].latest_checked_at = Some(Utc::now() - Duration::minutes(REGION_FRESHNESS_MINUTES + 1));
+        assert_eq!(service_state(&service, &[]), Up);
+    }
+
+    #[test]
+    fn service_state_is_degraded_by_an_expiring_certificate() {
+        let mut service = service(true);
+        service.certificate_expires_at = Some(Utc::now() + Duration::days(3));
+        assert_eq!(service_state(&service, &[]), Degraded);
+
+        service.certificate_expires_at = Some(Utc::now() + Duration::days(30));
+        assert_eq!(service_state(&service, &[]), Up);
+    }
+
+    #[test]
+    fn services_down_counts_the_derived_state() {
+        let mut partial = service(false);
+        partial.region_quorum = Some(2);
+        partial.checks.regions = vec![region("eu", false), region("us", true)];
+        let mut critical = service(true);
+        critical.id = "web".to_string();
+
+        let overview = build_overview(&[partial, critical], &[], vec![incident("web", IncidentImpact::Critical, false)], &[]);
+        assert_eq!(overview.services.iter().map(|s| s.state).collect::<Vec<_>>(), [Partial, Down]);
+        assert_eq!(services_down(&overview.services), 1);
+    }
+
+    #[test]
+    fn status_of_an_empty_set_is_up() {
+        assert_eq!(status_of(std::iter::empty()), Up);
+    }
+
+    #[test]
+    fn status_of_takes_the_worst_state() {
+        assert_eq!(status_of([Up, Degraded, Up].into_iter()), Degraded);
+        assert_eq!(status_of([Degraded, Partial].into_iter()), Partial);
+    }
+
+    #[test]
+    fn status_of_is_down_only_when_everything_is_down() {
+        assert_eq!(status_of([Down, Down].into_iter()), Down);
+        assert_eq!(status_of([Down, Up].into_iter()), Partial);
+        assert_eq!(status_of([Down, Degraded].into_iter()), Partial);
+    }
+}